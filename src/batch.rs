@@ -0,0 +1,88 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use crate::error::{Error, Result};
+use crate::input;
+use crate::isolate::{self, Outcome};
+use crate::params::Overrides;
+use crate::solution::Runnable;
+
+/// Outcome of a solution on one of the inputs, errors don't stop the other inputs
+pub struct Batch {
+    /// File name of the input, relative to the directory
+    pub file: String,
+    pub run: Result<Outcome>,
+}
+
+/// Files of the directory in alphabetical order, hidden files and subdirectories are skipped
+pub fn inputs(directory: &Path) -> Result<Vec<PathBuf>> {
+    let error = |err: std::io::Error| Error::Input { path: directory.display().to_string(), message: err.to_string() };
+    let mut paths = vec![];
+    for entry in fs::read_dir(directory).map_err(error)? {
+        let path = entry.map_err(error)?.path();
+        if path.is_file() && !path.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.')) {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+/// Run the solution on every input, each in its own thread, results are in the order of the inputs
+///
+/// Every input is [isolated](isolate::run) with the time limit, so a hanging one doesn't hold up the rest.
+pub fn run(solution: &'static dyn Runnable, paths: &[PathBuf], overrides: &Overrides, strict: bool, limit: Option<Duration>) -> Vec<Batch> {
+    thread::scope(|scope| {
+        let workers = paths
+            .iter()
+            .map(|path| {
+                let worker = scope.spawn(move || {
+                    let path = path.to_string_lossy();
+                    isolate::run(solution, input::load(Some(&path), solution.input(), strict)?, overrides.clone(), limit)
+                });
+                (path, worker)
+            })
+            .collect::<Vec<_>>();
+        workers
+            .into_iter()
+            .map(|(path, worker)| Batch {
+                file: path.file_name().unwrap_or(path.as_os_str()).to_string_lossy().to_string(),
+                run: worker.join().unwrap_or_else(|_| Err(Error::solve(solution.day(), "solution panicked"))),
+            })
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+
+    use crate::batch::{inputs, run, Batch};
+    use crate::isolate::Outcome;
+    use crate::params::Overrides;
+    use crate::solution::find;
+
+    #[test]
+    fn run_every_input() {
+        let directory = env::temp_dir().join(format!("batch_test_{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("b.txt"), "1\n2\n\n3\n").unwrap();
+        fs::write(directory.join("a.txt"), include_str!("resources/test/day_1_elves_inventory_example.txt")).unwrap();
+        fs::write(directory.join("c.txt"), "x\n").unwrap();
+        fs::write(directory.join(".hidden"), "").unwrap();
+
+        let batches = run(find(1).unwrap(), &inputs(&directory).unwrap(), &Overrides::default(), false, None);
+        fs::remove_dir_all(&directory).unwrap();
+        assert_eq!(batches.iter().map(|batch| batch.file.as_str()).collect::<Vec<&str>>(), vec!["a.txt", "b.txt", "c.txt"]);
+        let answers = |batch: &Batch| match &batch.run {
+            Ok(Outcome::Solved(run)) => run.parts.each_ref().map(|part| part.answer.clone()),
+            _ => panic!("{} wasn't solved", batch.file),
+        };
+        assert_eq!(answers(&batches[0])[0], "24000");
+        assert_eq!(answers(&batches[1]), ["3", "6"]);
+        assert!(batches[2].run.is_err());
+    }
+}
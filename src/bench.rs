@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::error::Result;
+use crate::params::Overrides;
+use crate::report::format_duration;
+use crate::solution::Runnable;
+
+pub const PHASES: [&str; 3] = ["parse", "part1", "part2"];
+
+/// Summary statistics of repeated measurements, in nanoseconds
+#[derive(Debug, PartialEq)]
+pub struct Stats {
+    pub min: f64,
+    pub median: f64,
+    pub mean: f64,
+    pub stddev: f64,
+}
+
+impl Stats {
+    pub fn new(samples: &[Duration]) -> Stats {
+        let mut nanos = samples.iter().map(|sample| sample.as_nanos() as f64).collect::<Vec<f64>>();
+        nanos.sort_by(|a, b| a.total_cmp(b));
+        let len = nanos.len() as f64;
+        let mean = nanos.iter().sum::<f64>() / len;
+        let middle = nanos.len() / 2;
+        let median = if nanos.len() % 2 == 0 { (nanos[middle - 1] + nanos[middle]) / 2.0 } else { nanos[middle] };
+        let variance = nanos.iter().map(|sample| (sample - mean).powi(2)).sum::<f64>() / len;
+        Stats { min: nanos[0], median, mean, stddev: variance.sqrt() }
+    }
+}
+
+/// Statistics of a single phase (parsing or one of the parts) of a day
+pub struct Measurement {
+    pub day: usize,
+    pub phase: &'static str,
+    pub stats: Stats,
+}
+
+/// Run the solution `iterations` times on the given input and measure each phase separately
+pub fn bench(solution: &dyn Runnable, input: &str, overrides: &Overrides, iterations: usize) -> Result<Vec<Measurement>> {
+    let mut samples: [Vec<Duration>; 3] = [vec![], vec![], vec![]];
+    for _ in 0..iterations {
+        let run = solution.run(input, overrides)?;
+        samples[0].push(run.parse_time);
+        samples[1].push(run.parts[0].time);
+        samples[2].push(run.parts[1].time);
+    }
+    Ok(PHASES
+        .iter()
+        .zip(samples.iter())
+        .map(|(phase, samples)| Measurement { day: solution.day(), phase, stats: Stats::new(samples) })
+        .collect())
+}
+
+/// Saved medians of a previous benchmark run keyed by day and phase
+///
+/// The file has a `day phase min median mean stddev` line per measurement, all times in nanoseconds.
+pub struct Baseline {
+    medians: HashMap<(usize, String), f64>,
+}
+
+impl Baseline {
+    /// Parse a saved baseline, the message says which line is malformed
+    pub fn parse(input: &str) -> std::result::Result<Baseline, String> {
+        let mut medians = HashMap::new();
+        for (index, line) in input.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parts = line.split_whitespace().collect::<Vec<&str>>();
+            let error = |what: &str| format!("line {}: couldn't parse {} of '{}'", index + 1, what, line);
+            let day = parts.first().and_then(|day| day.parse::<usize>().ok()).ok_or_else(|| error("day"))?;
+            let phase = parts.get(1).ok_or_else(|| error("phase"))?;
+            let median = parts.get(3).and_then(|median| median.parse::<f64>().ok()).ok_or_else(|| error("median"))?;
+            medians.insert((day, phase.to_string()), median);
+        }
+        Ok(Baseline { medians })
+    }
+
+    pub fn serialize(measurements: &[Measurement]) -> String {
+        let mut result = String::from("# day phase min median mean stddev (ns)\n");
+        for measurement in measurements {
+            let stats = &measurement.stats;
+            result += &format!(
+                "{} {} {:.0} {:.0} {:.0} {:.0}\n",
+                measurement.day, measurement.phase, stats.min, stats.median, stats.mean, stats.stddev
+            );
+        }
+        result
+    }
+
+    fn median(&self, day: usize, phase: &str) -> Option<f64> {
+        self.medians.get(&(day, phase.to_string())).copied()
+    }
+}
+
+/// Print the measurements and their change against the baseline, returns number of regressions
+///
+/// A regression is a median slower than the baseline one by more than `threshold` percent.
+pub fn print(measurements: &[Measurement], baseline: Option<&Baseline>, threshold: f64) -> usize {
+    let mut regressions = 0;
+    println!("{:>3} | {:<5} | {:>11} | {:>11} | {:>11} | {:>11} | Baseline", "Day", "Phase", "Min", "Median", "Mean", "Stddev");
+    for measurement in measurements {
+        let stats = &measurement.stats;
+        let comparison = match baseline.and_then(|baseline| baseline.median(measurement.day, measurement.phase)) {
+            Some(previous) => {
+                let change = (stats.median - previous) / previous * 100.0;
+                if change > threshold {
+                    regressions += 1;
+                    format!("{:+.1}% REGRESSION", change)
+                } else {
+                    format!("{:+.1}%", change)
+                }
+            }
+            None => String::new(),
+        };
+        let row = format!(
+            "{:>3} | {:<5} | {:>11} | {:>11} | {:>11} | {:>11} | {}",
+            measurement.day,
+            measurement.phase,
+            format_nanos(stats.min),
+            format_nanos(stats.median),
+            format_nanos(stats.mean),
+            format_nanos(stats.stddev),
+            comparison
+        );
+        println!("{}", row.trim_end());
+    }
+    regressions
+}
+
+fn format_nanos(nanos: f64) -> String {
+    format_duration(Duration::from_nanos(nanos.round() as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use crate::bench::{Baseline, Measurement, Stats};
+
+    #[test]
+    fn stats_of_samples() {
+        let samples = [4, 1, 3, 2].map(Duration::from_nanos);
+        let stats = Stats::new(&samples);
+        assert_eq!(stats.min, 1.0);
+        assert_eq!(stats.median, 2.5);
+        assert_eq!(stats.mean, 2.5);
+        assert_eq!(stats.stddev, 1.25_f64.sqrt());
+    }
+
+    #[test]
+    fn baseline_round_trip() {
+        let measurements = vec![Measurement { day: 9, phase: "part2", stats: Stats::new(&[Duration::from_nanos(1500)]) }];
+        let baseline = Baseline::parse(&Baseline::serialize(&measurements)).unwrap();
+        assert_eq!(baseline.median(9, "part2"), Some(1500.0));
+        assert_eq!(baseline.median(9, "part1"), None);
+        assert_eq!(Baseline::parse("# day phase\n9 part2 1500\n").err(), Some("line 2: couldn't parse median of '9 part2 1500'".to_string()));
+    }
+}
@@ -0,0 +1,216 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::error::{Error, Result};
+use crate::log;
+use crate::simulation::Simulation;
+
+/// Simulation whose full state can be saved to a file, so a long run can continue after it was interrupted
+pub trait Checkpoint: Simulation {
+    /// Full state as text, the input the simulation was created from isn't needed
+    fn save(&self) -> String;
+
+    /// Continue from a saved state, `None` when it doesn't belong to a simulation of the same input
+    fn load(&mut self, state: &str) -> Option<()>;
+}
+
+/// Where and how often long simulations save their state, and where they resume from
+///
+/// Every simulation has its own checkpoint file named after its label, next to the given path, e.g.
+/// `state-day-11-20-rounds-with-worry-divided-by-3.txt` for `state.txt`. The file starts with a
+/// `checkpoint <label>` line and a `step <number>` line, followed by the saved state. Only a
+/// simulation with the same label resumes from it, others start from the beginning.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Settings {
+    /// Path the files the state is written to are named after, the resumed one when not given
+    pub path: Option<PathBuf>,
+    /// Number of steps between two checkpoints
+    pub interval: usize,
+    pub resume: Option<PathBuf>,
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings { path: None, interval: 1000, resume: None }
+    }
+}
+
+static SETTINGS: Mutex<Option<Settings>> = Mutex::new(None);
+
+/// Settings used by [run_until], nothing is saved or resumed until they're configured
+pub fn configure(settings: Settings) {
+    *SETTINGS.lock().unwrap() = Some(settings);
+}
+
+/// Step the simulation like [Simulation::run_until], with checkpoints as [configured](configure)
+pub fn run_until<S: Checkpoint, P: FnMut(&S) -> bool>(simulation: &mut S, label: &str, predicate: P) -> Result<usize> {
+    let settings = SETTINGS.lock().unwrap().clone();
+    match settings {
+        Some(settings) => settings.run_until(simulation, label, predicate),
+        None => simulation.run_until(predicate),
+    }
+}
+
+impl Settings {
+    /// Step the simulation until the predicate holds or it's over, resuming from the checkpoint with
+    /// the same label and saving a new one every `interval` steps
+    ///
+    /// Returns the number of steps from the start of the simulation, including the resumed ones.
+    pub fn run_until<S: Checkpoint, P: FnMut(&S) -> bool>(&self, simulation: &mut S, label: &str, mut predicate: P) -> Result<usize> {
+        let mut steps = match &self.resume {
+            Some(path) => resume(simulation, label, &file(path, label))?,
+            None => 0,
+        };
+        let path = self.path.as_ref().or(self.resume.as_ref()).map(|path| file(path, label));
+        while !predicate(simulation) && simulation.step()? {
+            steps += 1;
+            if let Some(path) = path.as_ref().filter(|_| steps.is_multiple_of(self.interval.max(1))) {
+                save(simulation, label, steps, path)?;
+            }
+        }
+        Ok(steps)
+    }
+}
+
+/// Checkpoint file of the simulation with the label, the label is appended to the file name
+fn file(path: &Path, label: &str) -> PathBuf {
+    let slug = label
+        .split(|character: char| !character.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<&str>>()
+        .join("-")
+        .to_lowercase();
+    let stem = path.file_stem().map_or(String::from("checkpoint"), |stem| stem.to_string_lossy().to_string());
+    match path.extension() {
+        Some(extension) => path.with_file_name(format!("{}-{}.{}", stem, slug, extension.to_string_lossy())),
+        None => path.with_file_name(format!("{}-{}", stem, slug)),
+    }
+}
+
+/// Load the checkpoint when it has the label and return its step, 0 when there's none of this simulation
+fn resume<S: Checkpoint>(simulation: &mut S, label: &str, path: &Path) -> Result<usize> {
+    let error = |message: &str| Error::Input { path: path.display().to_string(), message: message.to_string() };
+    let checkpoint = match fs::read_to_string(path) {
+        Ok(checkpoint) => checkpoint,
+        Err(err) if err.kind() == ErrorKind::NotFound => {
+            log::info(format_args!("no checkpoint at {}, starting {} from the beginning", path.display(), label));
+            return Ok(0);
+        }
+        Err(err) => return Err(error(&err.to_string())),
+    };
+    let mut lines = checkpoint.splitn(3, '\n');
+    let header = lines.next().and_then(|line| line.strip_prefix("checkpoint ")).ok_or_else(|| error("not a checkpoint"))?;
+    if header != label {
+        log::info(format_args!("checkpoint is of {}, starting {} from the beginning", header, label));
+        return Ok(0);
+    }
+    let steps = lines
+        .next()
+        .and_then(|line| line.strip_prefix("step "))
+        .and_then(|steps| steps.parse().ok())
+        .ok_or_else(|| error("checkpoint doesn't say at which step it was saved"))?;
+    simulation
+        .load(lines.next().unwrap_or(""))
+        .ok_or_else(|| error(&format!("checkpoint of {} doesn't match the puzzle input", label)))?;
+    log::info(format_args!("resumed {} from step {}", label, steps));
+    Ok(steps)
+}
+
+/// Write the checkpoint next to the file first, so an interrupted write doesn't destroy the previous one
+fn save<S: Checkpoint>(simulation: &S, label: &str, steps: usize, path: &Path) -> Result<()> {
+    let partial = path.with_extension("partial");
+    let error = |path: &Path, err: std::io::Error| Error::Input { path: path.display().to_string(), message: err.to_string() };
+    fs::write(&partial, format!("checkpoint {}\nstep {}\n{}", label, steps, simulation.save())).map_err(|err| error(&partial, err))?;
+    fs::rename(&partial, path).map_err(|err| error(path, err))?;
+    log::debug(format_args!("saved {} at step {} to {}", label, steps, path.display()));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::path::Path;
+
+    use crate::checkpoint::{file, Checkpoint, Settings};
+    use crate::error::{Error, Result};
+    use crate::simulation::Simulation;
+
+    /// Sum of the numbers from 1 to the current one, which ends at 10
+    struct Sum {
+        number: usize,
+        sum: usize,
+    }
+
+    impl Simulation for Sum {
+        type State = (usize, usize);
+
+        fn step(&mut self) -> Result<bool> {
+            if self.number == 10 {
+                return Ok(false);
+            }
+            self.number += 1;
+            self.sum += self.number;
+            Ok(true)
+        }
+
+        fn snapshot(&self) -> (usize, usize) {
+            (self.number, self.sum)
+        }
+
+        fn restore(&mut self, (number, sum): (usize, usize)) {
+            (self.number, self.sum) = (number, sum);
+        }
+    }
+
+    impl Checkpoint for Sum {
+        fn save(&self) -> String {
+            format!("{} {}\n", self.number, self.sum)
+        }
+
+        fn load(&mut self, state: &str) -> Option<()> {
+            let (number, sum) = state.trim().split_once(' ')?;
+            (self.number, self.sum) = (number.parse().ok()?, sum.parse().ok()?);
+            Some(())
+        }
+    }
+
+    #[test]
+    fn save_and_resume() {
+        let path = env::temp_dir().join(format!("checkpoint_test_{}.txt", std::process::id()));
+        let file = env::temp_dir().join(format!("checkpoint_test_{}-sum.txt", std::process::id()));
+        let saving = Settings { path: Some(path.clone()), interval: 3, resume: None };
+        let mut sum = Sum { number: 0, sum: 0 };
+        assert_eq!(saving.run_until(&mut sum, "sum", |sum| sum.number == 7), Ok(7));
+        assert_eq!(fs::read_to_string(&file).unwrap(), "checkpoint sum\nstep 6\n6 21\n");
+
+        let resuming = Settings { path: None, interval: 3, resume: Some(path.clone()) };
+        let mut sum = Sum { number: 0, sum: 0 };
+        assert_eq!(resuming.run_until(&mut sum, "sum", |_| false), Ok(10));
+        assert_eq!(sum.sum, 55);
+        assert_eq!(fs::read_to_string(&file).unwrap(), "checkpoint sum\nstep 9\n9 45\n");
+
+        let mut other = Sum { number: 0, sum: 0 };
+        assert_eq!(resuming.run_until(&mut other, "other", |other| other.number == 2), Ok(2));
+        assert!(!path.exists());
+        fs::write(&file, "checkpoint other\nstep 9\n9 45\n").unwrap();
+        let mut sum = Sum { number: 0, sum: 0 };
+        assert_eq!(resuming.run_until(&mut sum, "sum", |sum| sum.number == 1), Ok(1));
+        fs::write(&file, "checkpoint sum\nstep 9\nnine\n").unwrap();
+        assert!(resuming.run_until(&mut other, "sum", |_| false).is_err());
+        fs::remove_file(file).unwrap();
+
+        let missing = Settings { path: Some(path.join("missing").join("state.txt")), interval: 3, resume: None };
+        let mut sum = Sum { number: 0, sum: 0 };
+        assert!(matches!(missing.run_until(&mut sum, "sum", |_| false), Err(Error::Input { .. })));
+    }
+
+    #[test]
+    fn file_per_label() {
+        let label = "day 14, sand from 500,0 until it falls past the rocks";
+        assert_eq!(file(Path::new("runs/state.txt"), label), Path::new("runs/state-day-14-sand-from-500-0-until-it-falls-past-the-rocks.txt"));
+        assert_eq!(file(Path::new("state"), "Day 11"), Path::new("state-day-11"));
+    }
+}
@@ -1,65 +1,158 @@
-#[allow(dead_code)]
-pub fn main() {
-    let inventory_str = include_str!("resources/day_1_elves_inventory.txt");
-    let (index, calories) = get_elf_with_most_food_in_calories(inventory_str);
-    println!("Elf #{} has the most calories ({})", index, calories);
-
-    let top_3_elves = get_3_elves_with_most_food_in_calories(inventory_str);
-    let mut total = 0;
-    for (index, calories) in &top_3_elves {
-        println!("Elf #{} belongs to top 3 with {}", index, calories);
-        total += calories
+use crate::error::{Error, Result};
+use crate::explain;
+use crate::params::{self, Params};
+use crate::random::Random;
+use crate::solution::Solution;
+
+/// Day 1: Calorie Counting, elves carrying the most calories
+pub struct Day1;
+
+impl Solution for Day1 {
+    type Input = Vec<u32>;
+    type Params = Day1Params;
+
+    fn name(&self) -> &'static str {
+        "Calorie Counting"
+    }
+
+    fn day(&self) -> usize {
+        1
+    }
+
+    fn input(&self) -> &'static str {
+        "day_1_elves_inventory.txt"
+    }
+
+    fn parse(&self, inventory_str: &str) -> Result<Vec<u32>> {
+        parse_inventory(inventory_str)
+    }
+
+    fn part1(&self, totals: &Vec<u32>, _params: &Day1Params) -> Result<String> {
+        let (elf, calories) = get_elf_with_most_food_in_calories(totals)
+            .ok_or_else(|| Error::solve(1, "There are no elves in the inventory"))?;
+        explain::note("elf carrying the most", format!("elf {} with {} calories", elf + 1, calories));
+        Ok(calories.to_string())
+    }
+
+    fn part2(&self, totals: &Vec<u32>, params: &Day1Params) -> Result<String> {
+        let elves = get_elves_with_most_food_in_calories(totals, params.top);
+        if explain::enabled() {
+            for (rank, (elf, calories)) in elves.iter().enumerate() {
+                explain::note(&format!("top {}", rank + 1), format!("elf {} with {} calories", elf + 1, calories));
+            }
+        }
+        Ok(elves.iter().map(|(_, calories)| calories).sum::<u32>().to_string())
+    }
+
+    fn generate(&self, random: &mut Random, size: usize) -> String {
+        generate(random, size)
+    }
+}
+
+pub struct Day1Params {
+    /// Number of elves carrying the most whose calories are summed in the second part
+    pub top: usize,
+}
+
+impl Default for Day1Params {
+    fn default() -> Day1Params {
+        Day1Params { top: 3 }
+    }
+}
+
+impl Params for Day1Params {
+    fn set(&mut self, key: &str, value: &str) -> std::result::Result<(), String> {
+        match key {
+            "top" => self.top = params::value(key, value)?,
+            _ => return Err(format!("unknown parameter '{}', use top", key)),
+        }
+        Ok(())
     }
-    println!("Top 3 elves have {} calories in total", total);
 }
 
-fn get_elf_with_most_food_in_calories(inventory_str: &str) -> (usize, u32) {
-    get_3_elves_with_most_food_in_calories(inventory_str)[0]
+/// Total calories carried by each elf, elves are separated by an empty line
+pub fn parse_inventory(inventory_str: &str) -> Result<Vec<u32>> {
+    let mut totals = vec![];
+    let mut current = None;
+    for (index, line) in inventory_str.lines().enumerate() {
+        if line.is_empty() {
+            totals.extend(current.take());
+            continue;
+        }
+        let calories = line
+            .parse::<u32>()
+            .map_err(|_| Error::parse(1, index + 1, line, "Couldn't parse calories input to u32"))?;
+        current = Some(current.unwrap_or(0) + calories);
+    }
+    totals.extend(current);
+    Ok(totals)
 }
 
-fn get_3_elves_with_most_food_in_calories(inventory_str: &str) -> Vec<(usize, u32)> {
-    let mut totals: Vec<(usize, u32)> = inventory_str
-        .split("\n\n")
-        .map(|inv: &str| inv
-            .lines()
-            .map(|cal: &str| cal.parse::<u32>().expect("Couldn't parse calories input to u32"))
-            .fold(0, |acc: u32, x: u32| acc + x)
-        )
-        .enumerate()
-        .collect();
+/// Index and calories of the elf carrying the most, `None` without any elves
+pub fn get_elf_with_most_food_in_calories(totals: &[u32]) -> Option<(usize, u32)> {
+    get_elves_with_most_food_in_calories(totals, 1).first().copied()
+}
+
+/// Index and calories of (up to) three elves carrying the most, the biggest first
+pub fn get_3_elves_with_most_food_in_calories(totals: &[u32]) -> Vec<(usize, u32)> {
+    get_elves_with_most_food_in_calories(totals, 3)
+}
+
+/// Index and calories of (up to) `count` elves carrying the most, the biggest first
+pub fn get_elves_with_most_food_in_calories(totals: &[u32], count: usize) -> Vec<(usize, u32)> {
+    let mut totals = totals.iter().copied().enumerate().collect::<Vec<(usize, u32)>>();
     totals.sort_by(|(_, a), (_, b)| b.cmp(a));
-    totals.truncate(3);
+    totals.truncate(count);
     totals
 }
 
+/// Random inventory of `size` elves, each carrying a few snacks
+pub fn generate(random: &mut Random, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            (0..random.range(1..6))
+                .map(|_| random.range(1000..60000).to_string())
+                .collect::<Vec<String>>()
+                .join("\n")
+        })
+        .collect::<Vec<String>>()
+        .join("\n\n")
+        + "\n"
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::day_1::{get_3_elves_with_most_food_in_calories, get_elf_with_most_food_in_calories};
+    use crate::day_1::{get_3_elves_with_most_food_in_calories, get_elf_with_most_food_in_calories, parse_inventory};
+    use crate::error::Error;
 
     #[test]
     fn example_inventory_max_calories() {
         let inventory_str = include_str!("resources/test/day_1_elves_inventory_example.txt");
-        let (index, calories) = get_elf_with_most_food_in_calories(inventory_str);
+        let (index, calories) = get_elf_with_most_food_in_calories(&parse_inventory(inventory_str).unwrap()).unwrap();
         let results: [u32; 5] = [6000, 4000, 11000, 24000, 10000];
         for (idx, result) in results.iter().enumerate() {
             if 24000.eq(result) {
                 assert_eq!(index, idx);
-                assert_eq!(calories, result.clone());
+                assert_eq!(calories, *result);
                 continue;
             }
             assert_ne!(index, idx);
-            assert_ne!(calories, result.clone());
+            assert_ne!(calories, *result);
         }
     }
 
     #[test]
     fn example_inventory_top_3_calories() {
         let inventory_str = include_str!("resources/test/day_1_elves_inventory_example.txt");
-        let top_3_elves = get_3_elves_with_most_food_in_calories(inventory_str);
+        let top_3_elves = get_3_elves_with_most_food_in_calories(&parse_inventory(inventory_str).unwrap());
         let results: [u32; 3] = [24000, 11000, 10000];
         for (idx, result) in results.iter().enumerate() {
-            assert_eq!(top_3_elves[idx].1, result.clone());
+            assert_eq!(top_3_elves[idx].1, *result);
         }
     }
-}
 
+    #[test]
+    fn malformed_calories() {
+        assert_eq!(parse_inventory("1000\n\n20x0\n"), Err(Error::parse(1, 3, "20x0", "Couldn't parse calories input to u32")));
+    }
+}
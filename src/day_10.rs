@@ -1,78 +1,317 @@
 use std::str::FromStr;
+use crate::error::{Error, Result};
+use crate::random::Random;
+use crate::geometry::Point;
+use crate::grid::SparseGrid;
+use crate::params::{self, Params};
+use crate::simulation::{Drawable, Driver, Simulation, Traceable};
+use crate::solution::Solution;
+use crate::trace::Trace;
+use crate::visualize::{Frame, Visualizer};
 
-#[allow(dead_code)]
-pub fn main() {
-    let input = include_str!("resources/day_10_cpu_instructions.txt");
+/// Day 10: Cathode-Ray Tube, signal strength and the CRT screen drawn by a program
+pub struct Day10;
 
-    let mut cpu = CPU::new();
-    for line in input.lines() {
-        cpu.parse(line);
+impl Solution for Day10 {
+    type Input = Vec<Instruction>;
+    type Params = Day10Params;
+
+    fn name(&self) -> &'static str {
+        "Cathode-Ray Tube"
+    }
+
+    fn day(&self) -> usize {
+        10
+    }
+
+    fn input(&self) -> &'static str {
+        "day_10_cpu_instructions.txt"
+    }
+
+    fn parse(&self, input: &str) -> Result<Vec<Instruction>> {
+        parse_program(input)
+    }
+
+    fn part1(&self, program: &Vec<Instruction>, params: &Day10Params) -> Result<String> {
+        Ok(run_program(program, params)?.get_memory().iter().sum::<isize>().to_string())
+    }
+
+    fn part2(&self, program: &Vec<Instruction>, params: &Day10Params) -> Result<String> {
+        Ok(run_program(program, params)?.get_screen().trim_end().to_string())
+    }
+
+    fn generate(&self, random: &mut Random, size: usize) -> String {
+        generate(random, size)
+    }
+
+    fn visualize(&self, program: &Vec<Instruction>, params: &Day10Params) -> Option<Box<dyn Visualizer>> {
+        Some(Box::new(Driver::new(Device::new(program, params))))
+    }
+
+    /// Register after every cycle
+    fn trace(&self, program: &Vec<Instruction>, params: &Day10Params) -> Result<Option<Trace>> {
+        Trace::record(&mut Device::new(program, params), usize::MAX).map(Some)
+    }
+}
+
+#[derive(Clone)]
+pub struct Day10Params {
+    /// Cycles during which the signal strength is sampled
+    pub cycles: Vec<usize>,
+    /// Number of pixels in a row of the screen
+    pub width: usize,
+}
+
+impl Default for Day10Params {
+    fn default() -> Day10Params {
+        Day10Params { cycles: vec![20, 60, 100, 140, 180, 220], width: 40 }
+    }
+}
+
+impl Params for Day10Params {
+    fn set(&mut self, key: &str, value: &str) -> std::result::Result<(), String> {
+        match key {
+            "cycles" => self.cycles = params::list(key, value)?,
+            "width" => self.width = params::value(key, value).ok().filter(|width| *width > 0).ok_or("parameter 'width' has to be a positive number")?,
+            _ => return Err(format!("unknown parameter '{}', use cycles or width", key)),
+        }
+        Ok(())
+    }
+}
+
+/// Instruction of the handheld device, `addx` takes two cycles
+#[derive(Clone, Debug, PartialEq)]
+pub enum Instruction {
+    Noop,
+    Addx(isize),
+}
+
+impl Instruction {
+    pub fn parse(line: &str) -> Option<Instruction> {
+        match line.split_whitespace().collect::<Vec<&str>>()[..] {
+            ["noop"] => Some(Instruction::Noop),
+            ["addx", value] => isize::from_str(value).ok().map(Instruction::Addx),
+            _ => None,
+        }
     }
-    println!("The sum of significant cycles is {}", cpu.get_memory().iter().fold(0, |acc, x| acc + x));
 }
 
-struct CPU {
-    cycle: u16,
+/// Instructions of the program, one per line
+pub fn parse_program(input: &str) -> Result<Vec<Instruction>> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(index, line)| Instruction::parse(line).ok_or_else(|| Error::parse(10, index + 1, line, "Expected 'noop' or 'addx <number>'")))
+        .collect()
+}
+
+/// Execute the whole program on a fresh CPU
+pub fn run_program(program: &[Instruction], params: &Day10Params) -> Result<CPU> {
+    let mut device = Device::new(program, params);
+    device.run_until(|_| false)?;
+    Ok(device.cpu)
+}
+
+/// CPU with a single register driving the CRT, one pixel is drawn every cycle
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone)]
+pub struct CPU {
+    cycle: usize,
     register_x: isize,
     memory: Vec<isize>,
+    screen: String,
+    sampled_cycles: Vec<usize>,
+    width: usize,
 }
 
 impl CPU {
-    fn new() -> CPU {
+    pub fn new() -> CPU {
+        CPU::with_params(&Day10Params::default())
+    }
+
+    pub fn with_params(params: &Day10Params) -> CPU {
         CPU {
             cycle: 1,
             register_x: 1,
             memory: vec![],
+            screen: String::new(),
+            sampled_cycles: params.cycles.clone(),
+            width: params.width,
         }
     }
 
-    fn parse(&mut self, line: &str) {
-        if line.contains("noop") {
-            self.tick(0);
-        } else {
-            self.tick(0);
-            let value = line.split_whitespace().collect::<Vec<&str>>().last().unwrap().clone();
-            self.tick(isize::from_str(value).unwrap());
+    pub fn execute(&mut self, instruction: &Instruction) {
+        match instruction {
+            Instruction::Noop => self.tick(0),
+            Instruction::Addx(value) => {
+                self.tick(0);
+                self.tick(*value);
+            }
         }
     }
 
     fn tick(&mut self, value: isize) {
-        if vec![20, 60, 100, 140, 180, 220].contains(&self.cycle) {
+        if self.sampled_cycles.contains(&self.cycle) {
             self.memory.push(self.register_x * (self.cycle as isize));
         }
 
-        if ((self.cycle as isize - 1) % 40) >= (self.register_x - 1) && ((self.cycle as isize - 1) % 40) <= (self.register_x + 1) {
-            print!("#");
+        let width = self.width as isize;
+        if ((self.cycle as isize - 1) % width) >= (self.register_x - 1) && ((self.cycle as isize - 1) % width) <= (self.register_x + 1) {
+            self.screen.push('#');
         } else {
-            print!(".");
+            self.screen.push('.');
         }
 
-        if (self.cycle % 40) == 0 {
-            println!();
+        if self.cycle.is_multiple_of(self.width) {
+            self.screen.push('\n');
         }
 
         self.cycle += 1;
         self.register_x += value;
     }
 
-    fn get_memory(&self) -> Vec<isize> {
+    /// Signal strengths recorded during the sampled cycles, the 20th, 60th, 100th, 140th, 180th and 220th by default
+    pub fn get_memory(&self) -> Vec<isize> {
         self.memory.clone()
     }
+
+    /// Number of the cycle which is about to start
+    pub fn get_cycle(&self) -> usize {
+        self.cycle
+    }
+
+    /// Middle of the 3 pixels wide sprite
+    pub fn get_register_x(&self) -> isize {
+        self.register_x
+    }
+
+    /// Pixels drawn so far, each row of 40 pixels (by default) ends with a new line
+    pub fn get_screen(&self) -> &str {
+        &self.screen
+    }
+}
+
+impl Default for CPU {
+    fn default() -> Self {
+        CPU::new()
+    }
+}
+
+/// Handheld device running a program, one cycle at a time
+#[derive(Clone)]
+pub struct Device {
+    cpu: CPU,
+    program: Vec<Instruction>,
+    executed: usize,
+    /// Value added to the register at the end of the second cycle of `addx`
+    pending: Option<isize>,
+}
+
+impl Device {
+    pub fn new(program: &[Instruction], params: &Day10Params) -> Device {
+        Device { cpu: CPU::with_params(params), program: program.to_vec(), executed: 0, pending: None }
+    }
+}
+
+impl Simulation for Device {
+    type State = Device;
+
+    fn step(&mut self) -> Result<bool> {
+        if let Some(value) = self.pending.take() {
+            self.cpu.tick(value);
+            return Ok(true);
+        }
+        let Some(instruction) = self.program.get(self.executed) else {
+            return Ok(false);
+        };
+        self.executed += 1;
+        match instruction {
+            Instruction::Noop => self.cpu.tick(0),
+            Instruction::Addx(value) => {
+                self.cpu.tick(0);
+                self.pending = Some(*value);
+            }
+        }
+        Ok(true)
+    }
+
+    fn snapshot(&self) -> Device {
+        self.clone()
+    }
+
+    fn restore(&mut self, state: Device) {
+        *self = state;
+    }
+}
+
+impl Traceable for Device {
+    fn trace(&self) -> String {
+        format!("cycle {} | X {} | instruction {}", self.cpu.get_cycle(), self.cpu.get_register_x(), self.executed)
+    }
+}
+
+impl Drawable for Device {
+    /// Screen with pixels not drawn yet left blank, the sprite is highlighted on the row being drawn
+    fn frame(&self) -> Frame {
+        let mut cells = SparseGrid::new();
+        let rows = self.cpu.get_screen().lines().count().max(6);
+        let width = self.cpu.width as isize;
+        for y in 0..rows as isize {
+            for x in 0..width {
+                cells.insert(Point::new(x, y), ' ');
+            }
+        }
+        for (y, row) in self.cpu.get_screen().lines().enumerate() {
+            for (x, pixel) in row.chars().enumerate() {
+                cells.insert(Point::new(x as isize, y as isize), pixel);
+            }
+        }
+        let row = ((self.cpu.get_cycle() - 1) / self.cpu.width) as isize;
+        let sprite = self.cpu.get_register_x();
+        Frame {
+            cells,
+            highlighted: (sprite - 1..=sprite + 1).map(|x| Point::new(x, row)).collect(),
+            focus: Point::new(width / 2, row),
+            caption: format!("cycle {}, X = {}, instruction {} of {}", self.cpu.get_cycle(), sprite, self.executed, self.program.len()),
+        }
+    }
+}
+
+/// Random program of `size` instructions
+pub fn generate(random: &mut Random, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| if random.chance(1, 3) { "noop\n".to_string() } else { format!("addx {}\n", random.range(0..21) as isize - 10) })
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::day_10::CPU;
+    use crate::day_10::{parse_program, run_program, Day10Params, CPU};
+    use crate::error::Error;
 
     #[test]
     fn six_important_cycles_strength() {
         let input = include_str!("resources/test/day_10_cpu_instructions_example.txt");
 
         let mut cpu = CPU::new();
-        for line in input.lines() {
-            cpu.parse(line);
+        for instruction in parse_program(input).unwrap() {
+            cpu.execute(&instruction);
         }
         let correct_strengths = vec![420, 1140, 1800, 2940, 2880, 3960];
         assert_eq!(cpu.get_memory(), correct_strengths);
     }
+
+    #[test]
+    fn narrow_screen() {
+        let program = parse_program(include_str!("resources/test/day_10_cpu_instructions_example.txt")).unwrap();
+        let cpu = run_program(&program, &Day10Params { cycles: vec![20], width: 20 }).unwrap();
+        assert_eq!(cpu.get_memory(), vec![420]);
+        assert_eq!(cpu.get_screen().lines().count(), 12);
+        assert!(cpu.get_screen().starts_with("##..##..##..##..##..\n....................\n###...###...###...##\n"));
+    }
+
+    #[test]
+    fn unknown_instruction() {
+        assert_eq!(parse_program("noop\nmulx 3"), Err(Error::parse(10, 2, "mulx 3", "Expected 'noop' or 'addx <number>'")));
+    }
 }
@@ -1,49 +1,142 @@
-use std::str::FromStr;
-use regex::{Captures, Regex};
+use std::fmt::{self, Display, Formatter};
+use crate::checkpoint::{self, Checkpoint};
+use crate::error::{Error, Result};
+use crate::log;
+use crate::params::{self, Params};
+use crate::parse::{self, Parsed, Parser};
+use crate::random::Random;
+use crate::simulation::{Simulation, Traceable};
+use crate::solution::Solution;
+use crate::trace::Trace;
 
-pub fn main() {
-    let monkeys = include_str!("resources/day_11_monkeys.txt");
-    let mut game = KeepAway::new(monkeys, 3);
-    game.print();
-    for _ in 0..20 {
-        game.round();
+/// Day 11: Monkey in the Middle, monkeys throwing items based on worry levels
+pub struct Day11;
+
+impl Solution for Day11 {
+    type Input = Vec<Monkey>;
+    type Params = Day11Params;
+
+    fn name(&self) -> &'static str {
+        "Monkey in the Middle"
+    }
+
+    fn day(&self) -> usize {
+        11
     }
-    game.print();
-    println!("Monkey business with moderated stress is {}", game.get_monkey_business());
 
-    let mut game = KeepAway::new(monkeys, 1);
-    game.print();
-    for _ in 0..10000 {
-        game.round();
+    fn input(&self) -> &'static str {
+        "day_11_monkeys.txt"
     }
-    game.print();
-    println!("Monkey business is {}", game.get_monkey_business());
+
+    fn parse(&self, input: &str) -> Result<Vec<Monkey>> {
+        parse_monkeys(input)
+    }
+
+    fn part1(&self, monkeys: &Vec<Monkey>, params: &Day11Params) -> Result<String> {
+        play_keep_away(monkeys, params.relief, params.short_game)
+    }
+
+    fn part2(&self, monkeys: &Vec<Monkey>, params: &Day11Params) -> Result<String> {
+        play_keep_away(monkeys, 1, params.long_game)
+    }
+
+    fn generate(&self, random: &mut Random, size: usize) -> String {
+        generate(random, size)
+    }
+
+    /// Inventories after every round of the first part
+    fn trace(&self, monkeys: &Vec<Monkey>, params: &Day11Params) -> Result<Option<Trace>> {
+        Trace::record(&mut KeepAway::from_monkeys(monkeys.to_vec(), params.relief), params.short_game).map(Some)
+    }
+}
+
+pub struct Day11Params {
+    /// Rounds of the first part, worry levels are divided by `relief` after every inspection
+    pub short_game: usize,
+    pub relief: usize,
+    /// Rounds of the second part, there is no relief
+    pub long_game: usize,
+}
+
+impl Default for Day11Params {
+    fn default() -> Day11Params {
+        Day11Params { short_game: 20, relief: 3, long_game: 10000 }
+    }
+}
+
+impl Params for Day11Params {
+    fn set(&mut self, key: &str, value: &str) -> std::result::Result<(), String> {
+        match key {
+            "short_game" => self.short_game = params::value(key, value)?,
+            "long_game" => self.long_game = params::value(key, value)?,
+            "relief" => self.relief = params::value(key, value).ok().filter(|relief| *relief > 0).ok_or("parameter 'relief' has to be a positive number")?,
+            _ => return Err(format!("unknown parameter '{}', use short_game, relief or long_game", key)),
+        }
+        Ok(())
+    }
+}
+
+/// Monkey business after the given number of rounds, worry levels are divided by `stress_moderation` after every inspection
+pub fn play_keep_away(monkeys: &[Monkey], stress_moderation: usize, rounds: usize) -> Result<String> {
+    let mut game = KeepAway::from_monkeys(monkeys.to_vec(), stress_moderation);
+    log::debug(format_args!("monkeys before {} rounds\n{}", rounds, game));
+    let label = format!("day 11, {} rounds with worry divided by {}", rounds, stress_moderation);
+    checkpoint::run_until(&mut game, &label, |game| game.rounds == rounds)?;
+    log::debug(format_args!("monkeys after {} rounds\n{}", rounds, game));
+    game.get_monkey_business()
+        .map(|business| business.to_string())
+        .ok_or_else(|| Error::solve(11, "Monkey business needs at least two monkeys"))
 }
 
-struct KeepAway<'a> {
-    monkeys: Vec<Monkey<'a>>,
+/// Parse monkeys separated by an empty line, listed in the order of their indices, and check they
+/// only throw to existing monkeys
+pub fn parse_monkeys(input: &str) -> Result<Vec<Monkey>> {
+    let blocks = parse::blocks(input);
+    let monkeys = blocks.iter().map(|(line, monkey)| Monkey::parse(monkey, *line)).collect::<Result<Vec<Monkey>>>()?;
+
+    for (position, ((line, block), monkey)) in blocks.iter().zip(&monkeys).enumerate() {
+        let lines = block.lines().collect::<Vec<&str>>();
+        if monkey.index != position {
+            return Err(Error::parse(11, *line, lines[0], &format!("Monkey {} is listed where monkey {} was expected", monkey.index, position)));
+        }
+        for (offset, target) in [(4, monkey.yes), (5, monkey.no)] {
+            if target >= monkeys.len() {
+                return Err(Error::parse(11, line + offset, lines[offset], "Monkey throws to a monkey which doesn't exist"));
+            }
+        }
+    }
+    Ok(monkeys)
+}
+
+/// Game of keep away played by the monkeys
+#[derive(Clone)]
+pub struct KeepAway {
+    monkeys: Vec<Monkey>,
     common_divisor: usize,
+    rounds: usize,
 }
 
-impl<'a> KeepAway<'a> {
-    fn new(input: &str, stress_moderation: usize) -> KeepAway {
-        let monkeys = input.split("\n\n").map(|monkey| Monkey::parse(monkey, stress_moderation)).collect::<Vec<Monkey>>();
+impl KeepAway {
+    pub fn new(input: &str, stress_moderation: usize) -> Result<KeepAway> {
+        Ok(KeepAway::from_monkeys(parse_monkeys(input)?, stress_moderation))
+    }
+
+    pub fn from_monkeys(mut monkeys: Vec<Monkey>, stress_moderation: usize) -> KeepAway {
+        monkeys.iter_mut().for_each(|monkey| monkey.stress_moderation = stress_moderation);
         let mut divisors = monkeys.iter()
-            .map(|monkey| usize::from_str(monkey.captures.as_ref().unwrap().name("test").unwrap().as_str()).unwrap())
+            .map(|monkey| monkey.divisor)
             .collect::<Vec<usize>>();
         divisors.sort();
         divisors.dedup();
         KeepAway {
             monkeys,
-            common_divisor: divisors.iter().fold(1, |acc, div| acc * div),
+            common_divisor: divisors.iter().product::<usize>(),
+            rounds: 0,
         }
     }
 
-    fn print(&self) {
-        self.monkeys.iter().for_each(|monkey| monkey.print());
-    }
-
-    fn round(&mut self) {
+    /// Every monkey takes a turn, inspecting and throwing all of its items
+    pub fn round(&mut self) {
         let monkeys = &mut self.monkeys;
         for monkey in 0..monkeys.len() {
             for _ in 0..monkeys.get(monkey).unwrap().inventory.len() {
@@ -54,39 +147,115 @@ impl<'a> KeepAway<'a> {
         for monkey in 0..monkeys.len() {
             monkeys.get_mut(monkey).unwrap().update_items(self.common_divisor);
         }
+        self.rounds += 1;
     }
 
-    fn get_monkey_business(&self) -> usize {
+    /// Product of the number of inspections of the two most active monkeys
+    pub fn get_monkey_business(&self) -> Option<usize> {
         let mut inspected_list = self.monkeys.iter().map(|monkey| monkey.inspected).collect::<Vec<usize>>();
         inspected_list.sort_by(|a, b| b.cmp(a));
-        inspected_list.first().unwrap() * inspected_list.get(1).unwrap()
+        Some(inspected_list.first()? * inspected_list.get(1)?)
+    }
+}
+
+/// Every step is a round, the game never ends on its own
+impl Simulation for KeepAway {
+    type State = KeepAway;
+
+    fn step(&mut self) -> Result<bool> {
+        self.round();
+        Ok(true)
+    }
+
+    fn snapshot(&self) -> KeepAway {
+        self.clone()
+    }
+
+    fn restore(&mut self, state: KeepAway) {
+        *self = state;
     }
 }
 
-struct Monkey<'a> {
+/// Round and every monkey's inspection count and items, one monkey per line
+/// Each monkey is saved with its rules, so a checkpoint of another input isn't resumed
+impl Checkpoint for KeepAway {
+    fn save(&self) -> String {
+        let mut state = format!("round {}\n", self.rounds);
+        for monkey in &self.monkeys {
+            let items = monkey.inventory.iter().map(usize::to_string).collect::<Vec<String>>();
+            state.push_str(&format!("{} {} {} {}\n", monkey.index, monkey.rules(), monkey.inspected, items.join(",")));
+        }
+        state
+    }
+
+    fn load(&mut self, state: &str) -> Option<()> {
+        let mut lines = state.lines();
+        let rounds = lines.next()?.strip_prefix("round ")?.parse().ok()?;
+        let mut monkeys = self.monkeys.clone();
+        for monkey in monkeys.iter_mut() {
+            let mut fields = lines.next()?.split(' ');
+            if fields.next()?.parse::<usize>().ok()? != monkey.index || fields.next()? != monkey.rules() {
+                return None;
+            }
+            monkey.inspected = fields.next()?.parse().ok()?;
+            monkey.inventory = match fields.next() {
+                Some("") | None => vec![],
+                Some(items) => items.split(',').map(|item| item.parse().ok()).collect::<Option<Vec<usize>>>()?,
+            };
+            if monkey.inventory.iter().any(|item| *item >= self.common_divisor) {
+                return None;
+            }
+        }
+        if lines.next().is_some() {
+            return None;
+        }
+        (self.monkeys, self.rounds) = (monkeys, rounds);
+        Some(())
+    }
+}
+
+impl Traceable for KeepAway {
+    fn trace(&self) -> String {
+        let inventories = self.monkeys.iter().map(|monkey| {
+            let items = monkey.inventory.iter().map(usize::to_string).collect::<Vec<String>>();
+            format!("{}: [{}]", monkey.index, items.join(","))
+        });
+        format!("round {} | {}", self.rounds, inventories.collect::<Vec<String>>().join(" "))
+    }
+}
+
+impl Display for KeepAway {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.monkeys.iter().try_for_each(|monkey| writeln!(f, "{}", monkey))
+    }
+}
+
+/// Monkey with its items and rules for inspecting and throwing them
+#[derive(Clone)]
+pub struct Monkey {
     inventory: Vec<usize>,
     index: usize,
-    captures: Option<Captures<'a>>,
+    operator: char,
+    /// Right-hand side of the operation, `None` stands for the old value
+    operand: Option<usize>,
+    divisor: usize,
+    yes: usize,
+    no: usize,
     inspected: usize,
     stress_moderation: usize,
 }
 
-impl<'a> Monkey<'a> {
-    fn new(index: usize, starting_items: Vec<usize>, captures: Option<Captures>, stress_moderation: usize) -> Monkey {
-        Monkey {
-            inventory: starting_items,
-            index,
-            captures,
-            inspected: 0,
-            stress_moderation,
-        }
+impl Monkey {
+    /// Operation, test and throw targets in one word, e.g. `*old/13?1:3`
+    fn rules(&self) -> String {
+        let operand = self.operand.map_or(String::from("old"), |operand| operand.to_string());
+        format!("{}{}/{}?{}:{}", self.operator, operand, self.divisor, self.yes, self.no)
     }
 
     fn inspect(&mut self, item: usize) -> usize {
         self.inspected += 1;
-        let op = self.captures.as_ref().unwrap().name("op").unwrap().as_str();
-        let num = if op.contains("old") { item } else { usize::from_str(op.split_whitespace().collect::<Vec<&str>>().get(1).unwrap()).unwrap() };
-        if op.contains("*") {
+        let num = self.operand.unwrap_or(item);
+        if self.operator == '*' {
             (item * num) / self.stress_moderation
         } else {
             (item + num) / self.stress_moderation
@@ -94,39 +263,74 @@ impl<'a> Monkey<'a> {
     }
 
     fn test(&self, item: usize) -> usize {
-        let division = usize::from_str(self.captures.as_ref().unwrap().name("test").unwrap().as_str()).unwrap();
-        if (item % division) == 0 {
-            usize::from_str(self.captures.as_ref().unwrap().name("yes").unwrap().as_str()).unwrap()
+        if item.is_multiple_of(self.divisor) {
+            self.yes
         } else {
-            usize::from_str(self.captures.as_ref().unwrap().name("no").unwrap().as_str()).unwrap()
+            self.no
         }
     }
 
-    fn parse(input: &str, stress_moderation: usize) -> Monkey {
-        let regex = Regex::new("\
-            Monkey (?P<monkey_index>\\d):\\n\\s\\s\
-            Starting items: (?P<items>[\\w+,\\s]*)\\n\\s\\s\
-            Operation: new = old (?P<op>.*)\\n\\s\\s\
-            Test: divisible by (?P<test>\\d+)\\n\\s\\s\\s\\s\
-            If true: throw to monkey (?P<yes>\\d+)\\n\\s\\s\\s\\s\
-            If false: throw to monkey (?P<no>\\d+)\
-            ").unwrap();
+    /// Parse a single monkey description, `line` is the line number of its first line
+    pub fn parse(input: &str, line: usize) -> Result<Monkey> {
+        let lines = input.lines().collect::<Vec<&str>>();
+        if lines.len() != 6 {
+            return Err(Error::parse(11, line, lines.first().unwrap_or(&""), "Monkey description has to have 6 lines"));
+        }
+        let operation_format = "Operation has to be in the form of 'new = old * 19' or 'new = old + old'";
+
+        let index = parse::line(11, line, lines[0], |parser| {
+            let index = labeled_int(parser, "Monkey ")?;
+            parser.literal(":")?;
+            Ok(index)
+        })?;
 
-        let captures = regex.captures(input).unwrap();
-        Monkey::new(
-            usize::from_str(captures.name("monkey_index").unwrap().as_str()).unwrap(),
-            captures.name("items").unwrap().as_str().split(", ").map(|cap| usize::from_str(cap).unwrap()).collect::<Vec<usize>>(),
-            Some(captures),
-            stress_moderation,
-        )
-    }
+        let inventory = parse::line(11, line + 1, lines[1], |parser| {
+            parser.spaces();
+            parser.literal("Starting items:")?;
+            if parser.is_done() {
+                return Ok(vec![]);
+            }
+            parser.literal(" ")?;
+            parser
+                .separated(", ", Parser::int)
+                .map_err(|failure| failure.describe("Starting items have to be numbers separated by commas"))
+        })?;
 
-    fn print(&self) {
-        println!("{}:{} | {}", self.index, self.inventory.iter().fold("".to_string(), |acc, item| acc + " " + item.to_string().as_str()), self.inspected);
+        let (operator, operand) = parse::line(11, line + 2, lines[2], |parser| {
+            parser.spaces();
+            parser.literal("Operation: new = old ")?;
+            let operator = parser.one_of(&["*", "+"]).map_err(|failure| failure.describe(operation_format))?;
+            parser.literal(" ").map_err(|failure| failure.describe(operation_format))?;
+            if parser.eat("old") {
+                return Ok((operator, None));
+            }
+            let operand = parser.int().map_err(|failure| failure.describe("Operand has to be a number or old"))?;
+            Ok((operator, Some(operand)))
+        })?;
+
+        let divisor = parse::line(11, line + 3, lines[3], |parser| {
+            let divisor = labeled_int(parser, "Test: divisible by ")?;
+            if divisor == 0 {
+                return parser.fail("Divisor has to be a positive number");
+            }
+            Ok(divisor)
+        })?;
+
+        Ok(Monkey {
+            inventory,
+            index,
+            operator: operator.chars().next().unwrap(),
+            operand,
+            divisor,
+            yes: parse::line(11, line + 4, lines[4], |parser| labeled_int(parser, "If true: throw to monkey "))?,
+            no: parse::line(11, line + 5, lines[5], |parser| labeled_int(parser, "If false: throw to monkey "))?,
+            inspected: 0,
+            stress_moderation: 1,
+        })
     }
 
     fn turn(&mut self) -> (usize, usize) {
-        let mut item = self.inventory.first().unwrap().clone();
+        let mut item = *self.inventory.first().unwrap();
         self.inventory.remove(0);
         item = self.inspect(item);
         (self.test(item), item)
@@ -142,28 +346,106 @@ impl<'a> Monkey<'a> {
     }
 }
 
+/// Index, items and number of inspections, e.g. `0: 79 98 | 2`
+impl Display for Monkey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let items = self.inventory.iter().map(|item| format!(" {}", item)).collect::<String>();
+        write!(f, "{}:{} | {}", self.index, items, self.inspected)
+    }
+}
+
+/// Number after an indented label, like `  Test: divisible by 23`
+fn labeled_int(parser: &mut Parser, label: &str) -> Parsed<usize> {
+    parser.spaces();
+    parser.literal(label)?;
+    parser.int()
+}
+
+/// Random description of `size` monkeys (at least two)
+///
+/// Monkeys only throw to monkeys which already had their turn in the round (monkey 0 to any other)
+/// and only one of them squares, so an item is inspected at most twice a round and worry levels
+/// fit into `usize` even without moderation.
+pub fn generate(random: &mut Random, size: usize) -> String {
+    let monkeys = size.max(2);
+    let squaring = random.range(0..monkeys);
+    let mut description = vec![];
+    for index in 0..monkeys {
+        let items = (0..random.range(1..6)).map(|_| random.range(50..100).to_string()).collect::<Vec<String>>().join(", ");
+        let operation = if index == squaring { "* old".to_string() } else { format!("{} {}", random.choose(&['*', '+']), random.range(1..10)) };
+        let mut target = || if index == 0 { random.range(1..monkeys) } else { random.range(0..index) };
+        let (yes, no) = (target(), target());
+        description.push(format!(
+            "Monkey {}:\n  Starting items: {}\n  Operation: new = old {}\n  Test: divisible by {}\n    If true: throw to monkey {}\n    If false: throw to monkey {}\n",
+            index, items, operation, random.choose(&[2, 3, 5, 7, 11, 13, 17, 19]), yes, no
+        ));
+    }
+    description.join("\n")
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::day_11::KeepAway;
+    use crate::checkpoint::Checkpoint;
+    use crate::day_11::{parse_monkeys, KeepAway};
+    use crate::error::Error;
 
     #[test]
     fn count_monkey_business_moderated_stress() {
         let monkeys = include_str!("resources/test/day_11_monkeys_example.txt");
-        let mut game = KeepAway::new(monkeys, 3);
+        let mut game = KeepAway::new(monkeys, 3).unwrap();
         for _ in 0..20 {
             game.round();
         }
-        assert_eq!(game.get_monkey_business(), 10605);
+        assert_eq!(game.get_monkey_business(), Some(10605));
     }
 
     #[test]
     fn count_monkey_business() {
         let monkeys = include_str!("resources/test/day_11_monkeys_example.txt");
-        let mut game = KeepAway::new(monkeys, 1);
+        let mut game = KeepAway::new(monkeys, 1).unwrap();
         for _ in 0..10000 {
             game.round();
         }
-        assert_eq!(game.get_monkey_business(), 2713310158);
+        assert_eq!(game.get_monkey_business(), Some(2713310158));
     }
-}
 
+    #[test]
+    fn resume_from_checkpoint() {
+        let monkeys = include_str!("resources/test/day_11_monkeys_example.txt");
+        let mut game = KeepAway::new(monkeys, 1).unwrap();
+        for _ in 0..5000 {
+            game.round();
+        }
+        let mut resumed = KeepAway::new(monkeys, 1).unwrap();
+        assert_eq!(resumed.load(&game.save()), Some(()));
+        for _ in 0..5000 {
+            resumed.round();
+        }
+        assert_eq!((resumed.rounds, resumed.get_monkey_business()), (10000, Some(2713310158)));
+        assert_eq!(resumed.load("round 3\n0 *19/23?2:3 1 79\n"), None);
+
+        let state = KeepAway::new(monkeys, 1).unwrap().save();
+        assert!(state.starts_with("round 0\n0 *19/23?2:3 0 79,98\n1 +6/19?2:0 0 54,65,75,74\n"));
+        assert_eq!(resumed.load(&state.replace("*19/23", "*19/29")), None);
+        assert_eq!(resumed.load(&state.replace("79,98", "96577,98")), None);
+        assert_eq!(resumed.load(&state.replace("79,98", "96576,98")), Some(()));
+    }
+
+    #[test]
+    fn unknown_operation() {
+        let monkeys = include_str!("resources/test/day_11_monkeys_example.txt").replace("new = old * 19", "new = old / 19");
+        assert_eq!(
+            parse_monkeys(&monkeys).err(),
+            Some(Error::parse(11, 3, "  Operation: new = old / 19", "Operation has to be in the form of 'new = old * 19' or 'new = old + old'").at_column(24))
+        );
+    }
+
+    #[test]
+    fn throw_to_missing_monkey() {
+        let monkeys = include_str!("resources/test/day_11_monkeys_example.txt").replace("If true: throw to monkey 2", "If true: throw to monkey 7");
+        assert_eq!(parse_monkeys(&monkeys).err(), Some(Error::parse(11, 5, "    If true: throw to monkey 7", "Monkey throws to a monkey which doesn't exist")));
+
+        let monkeys = include_str!("resources/test/day_11_monkeys_example.txt").replace("Monkey 1:", "Monkey 5:");
+        assert_eq!(parse_monkeys(&monkeys).err(), Some(Error::parse(11, 8, "Monkey 5:", "Monkey 5 is listed where monkey 1 was expected")));
+    }
+}
@@ -1,108 +1,123 @@
-pub fn main() {
-    let input = include_str!("resources/day_12_height_map.txt");
-    let mut map = Map::new(input, 'S', true);
-    map.update_all_directions();
-    map.update_all_weights();
-    println!("End weight is {}", map.get_weights('E').first().unwrap());
-
-    let mut map = Map::new(input, 'E', false);
-    map.update_all_directions();
-    map.update_all_weights();
-    println!("'a' closest to end has weight {}", map.get_weights('a').iter().min().unwrap());
-}
+use crate::error::{Error, Result};
+use crate::geometry::{Direction, Point};
+use crate::grid::{Grid, SparseGrid};
+use crate::image::Picture;
+use crate::log::{self, Level};
+use crate::random::Random;
+use crate::search::{self, Search};
+use crate::simulation::{Drawable, Driver, Simulation};
+use crate::solution::Solution;
+use crate::visualize::{Frame, Visualizer};
 
-#[derive(Copy, Clone)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
-}
+/// Day 12: Hill Climbing Algorithm, shortest climb on a height map
+pub struct Day12;
+
+impl Solution for Day12 {
+    type Input = Map;
+    type Params = ();
+
+    fn name(&self) -> &'static str {
+        "Hill Climbing Algorithm"
+    }
+
+    fn day(&self) -> usize {
+        12
+    }
 
-impl ToString for Direction {
-    fn to_string(&self) -> String {
-        match self {
-            Direction::Up => "^".to_string(),
-            Direction::Down => "v".to_string(),
-            Direction::Left => "<".to_string(),
-            Direction::Right => ">".to_string(),
+    fn input(&self) -> &'static str {
+        "day_12_height_map.txt"
+    }
+
+    fn parse(&self, input: &str) -> Result<Map> {
+        Map::new(input)
+    }
+
+    fn part1(&self, map: &Map, _params: &()) -> Result<String> {
+        let search = map.climb(map.positions_of('S'));
+        if log::enabled(Level::Debug) {
+            if let Some(path) = search.goal().and_then(|end| search.path(end)) {
+                log::debug(format_args!("shortest path\n{}", map.draw(&path)));
+            }
         }
+        shortest(&search)
+    }
+
+    fn part2(&self, map: &Map, _params: &()) -> Result<String> {
+        shortest(&map.climb(map.positions_of('a')))
+    }
+
+    fn generate(&self, random: &mut Random, size: usize) -> String {
+        generate(random, size)
+    }
+
+    fn export(&self, map: &Map, _params: &()) -> Option<Picture> {
+        Some(map.distance_field())
+    }
+
+    fn visualize(&self, map: &Map, _params: &()) -> Option<Box<dyn Visualizer>> {
+        Some(Box::new(Driver::new(ClimbAnimation::new(map))))
     }
 }
 
-#[derive(Clone)]
-struct Position {
-    x: usize,
-    y: usize,
-    value: char,
-    weight: usize,
-    visited: bool,
-    directions: Vec<Direction>,
+fn shortest(search: &Search<Point>) -> Result<String> {
+    search
+        .goal_distance()
+        .map(|steps| steps.to_string())
+        .ok_or_else(|| Error::solve(12, "There is no path between start and end"))
 }
 
-struct Map {
-    map: Vec<Position>,
-    width: usize,
-    start: usize,
-    ascending: bool,
+/// Height map, `a` is the lowest and `z` the highest elevation, `S` and `E` are the start and end
+#[derive(Clone)]
+pub struct Map {
+    heights: Grid<char>,
 }
 
 impl Map {
-    fn new(input: &str, s: char, ascending: bool) -> Map {
-        let mut map = vec![];
-        let mut start: usize = 0;
-        for (list_index, line) in input.lines().enumerate() {
-            for (char_index, position) in line.chars().enumerate() {
-                map.push(Position {
-                    x: char_index,
-                    y: list_index,
-                    value: position,
-                    weight: usize::MAX,
-                    visited: false,
-                    directions: vec![],
-                });
-                if position == s {
-                    start = map.len() - 1;
-                }
+    pub fn new(input: &str) -> Result<Map> {
+        let heights = Grid::parse(12, input, "height map", |value| {
+            if value.is_ascii_lowercase() || value == 'S' || value == 'E' {
+                Ok(value)
+            } else {
+                Err("Heights have to be lowercase letters, S or E")
             }
-        }
-        Map { map, start, width: input.lines().last().unwrap().len(), ascending }
-    }
-
-    fn update_all_directions(&mut self) {
-        for position in 0..self.map.len() {
-            let mut directions = vec![];
-            for direction in vec![Direction::Right, Direction::Left, Direction::Up, Direction::Down] {
-                let me = self.map.get(position).unwrap();
-                let neighbor = self.get_neighbor(me.x, me.y, direction);
-                if neighbor.is_some() &&
-                    ((self.ascending && Map::get_value(neighbor.unwrap().value) as usize <= Map::get_value(me.value) + 1) ||
-                        (!self.ascending && Map::get_value(neighbor.unwrap().value) as usize >= Map::get_value(me.value) - 1)) {
-                    directions.push(direction)
-                }
+        })?;
+        for required in ['S', 'E'] {
+            if heights.find(|value| *value == required).is_none() {
+                return Err(Error::parse(12, 1, input.lines().next().unwrap_or(""), &format!("Height map has no {} position", required)));
             }
-            self.map.get_mut(position).unwrap().directions = directions;
         }
+        Ok(Map { heights })
     }
 
-    fn update_all_weights(&mut self) {
-        let mut todo: Vec<usize> = vec![self.start.clone()];
-        self.map.get_mut(self.start).unwrap().weight = 0;
-        while todo.len() != 0 {
-            let item = todo.remove(0);
-            self.map.get_mut(item).unwrap().visited = true;
-            let me = self.map.get(item).unwrap().clone();
-            for direction in me.directions {
-                let width = self.width.clone();
-                let neighbor = self.get_mut_neighbor(me.x, me.y, direction).unwrap();
-                if !neighbor.visited && !todo.contains(&(neighbor.y * width + neighbor.x)) {
-                    todo.push(neighbor.y * width + neighbor.x);
-                }
-                if neighbor.weight > me.weight + 1 {
-                    neighbor.weight = me.weight + 1;
-                }
-            }
+    /// All positions marked with `value`
+    pub fn positions_of(&self, value: char) -> Vec<Point> {
+        self.heights.iter().filter(|(_, height)| **height == value).map(|(position, _)| position).collect()
+    }
+
+    /// Breadth-first search from the closest of the starts to `E`, climbing at most one higher on every step
+    pub fn climb(&self, starts: Vec<Point>) -> Search<Point> {
+        self.search(starts, |position| self.heights[position] == 'E')
+    }
+
+    fn search<G: FnMut(Point) -> bool>(&self, starts: Vec<Point>, goal: G) -> Search<Point> {
+        let neighbors = |position: Point| {
+            let highest = Map::get_value(self.heights[position]) + 1;
+            self.heights
+                .neighbors_4(position)
+                .filter(move |neighbor| Map::get_value(self.heights[*neighbor]) <= highest)
+        };
+        search::bfs(starts, neighbors, goal)
+    }
+
+    /// Distances of all positions reachable from `S` relative to the farthest one
+    pub fn distance_field(&self) -> Picture {
+        let search = self.search(self.positions_of('S'), |_| false);
+        let farthest = search.distances().values().max().copied().unwrap_or(0).max(1) as f64;
+        let mut picture = Grid::new(self.heights.width(), self.heights.height(), None);
+        for (position, distance) in search.distances() {
+            picture[*position] = Some(*distance as f64 / farthest);
         }
+        picture
     }
 
     fn get_value(value: char) -> usize {
@@ -115,70 +130,158 @@ impl Map {
         value as usize
     }
 
-    fn get_neighbor(&self, x: usize, y: usize, direction: Direction) -> Option<&Position> {
-        let (neighbor_x, neighbor_y) = match direction {
-            Direction::Left => (if x == 0 { usize::MAX } else { x - 1 }, y),
-            Direction::Right => (x + 1, y),
-            Direction::Up => (x, if y == 0 { usize::MAX } else { y - 1 }),
-            Direction::Down => (x, y + 1),
-        };
-        self.get_position(neighbor_x, neighbor_y)
+    /// The map with the path drawn over it as arrows
+    fn draw(&self, path: &[Point]) -> Grid<char> {
+        let mut map = self.heights.clone();
+        for step in path.windows(2) {
+            let direction = Direction::ORTHOGONAL.into_iter().find(|direction| step[0].step(*direction) == step[1]).unwrap();
+            map[step[0]] = direction.to_string().chars().next().unwrap();
+        }
+        map
     }
+}
 
-    fn get_mut_neighbor(&mut self, x: usize, y: usize, direction: Direction) -> Option<&mut Position> {
-        let (neighbor_x, neighbor_y) = match direction {
-            Direction::Left => (if x == 0 { usize::MAX } else { x - 1 }, y),
-            Direction::Right => (x + 1, y),
-            Direction::Up => (x, if y == 0 { usize::MAX } else { y - 1 }),
-            Direction::Down => (x, y + 1),
-        };
-        self.get_mut_position(neighbor_x, neighbor_y)
+/// Breadth-first search from `S` revealed one distance at a time, ending with the shortest path
+pub struct ClimbAnimation {
+    map: Map,
+    /// Positions reached by the search grouped by their distance
+    layers: Vec<Vec<Point>>,
+    path: Vec<Point>,
+    shown: usize,
+}
+
+impl ClimbAnimation {
+    pub fn new(map: &Map) -> ClimbAnimation {
+        let search = map.climb(map.positions_of('S'));
+        let mut layers = vec![];
+        for (position, distance) in search.distances() {
+            if layers.len() <= *distance {
+                layers.resize(distance + 1, vec![]);
+            }
+            layers[*distance].push(*position);
+        }
+        let path = search.goal().and_then(|end| search.path(end)).unwrap_or_default();
+        ClimbAnimation { map: map.clone(), layers, path, shown: 0 }
     }
+}
 
-    fn get_position(&self, x: usize, y: usize) -> Option<&Position> {
-        if x >= self.width || y >= self.get_height() {
-            return None;
+impl Simulation for ClimbAnimation {
+    type State = usize;
+
+    fn step(&mut self) -> Result<bool> {
+        if self.shown == self.layers.len() {
+            return Ok(false);
         }
+        self.shown += 1;
+        Ok(true)
+    }
 
-        Some(self.map.get(y * self.width + x).unwrap())
+    fn snapshot(&self) -> usize {
+        self.shown
     }
 
-    fn get_mut_position(&mut self, x: usize, y: usize) -> Option<&mut Position> {
-        if x >= self.width || y >= self.get_height() {
-            return None;
+    fn restore(&mut self, shown: usize) {
+        self.shown = shown;
+    }
+}
+
+impl Drawable for ClimbAnimation {
+    /// Reached positions are drawn in uppercase with the frontier highlighted, the path once the search is over
+    fn frame(&self) -> Frame {
+        let finished = self.shown == self.layers.len();
+        let mut cells = SparseGrid::from(&self.map.heights);
+        for position in self.layers[..self.shown].iter().flatten() {
+            cells.insert(*position, self.map.heights[*position].to_ascii_uppercase());
         }
+        if finished {
+            cells = SparseGrid::from(&self.map.draw(&self.path));
+        }
+
+        let frontier = if finished { &self.path } else { &self.layers[self.shown] };
+        Frame {
+            cells,
+            highlighted: frontier.iter().copied().collect(),
+            focus: frontier.first().copied().unwrap_or_default(),
+            caption: if finished {
+                format!("shortest path has {} steps", self.path.len().saturating_sub(1))
+            } else {
+                format!("distance {}, frontier of {} positions", self.shown, frontier.len())
+            },
+        }
+    }
+}
 
-        Some(self.map.get_mut(y * self.width + x).unwrap())
+/// Random height map `size` columns wide (at least 28) with a climbable path from S to E
+///
+/// The path goes column by column from left to right and climbs by one on 25 of its steps, never
+/// on the first two so there is an `a` for the hike to end on. The rest of the map is random.
+pub fn generate(random: &mut Random, size: usize) -> String {
+    let width = size.max(28);
+    let height = (size / 2).max(5);
+    let mut map = Grid::new(width, height, 'a');
+    for position in map.positions().collect::<Vec<Point>>() {
+        *map.get_mut(position).unwrap() = char::from(b'a' + random.range(0..26) as u8);
     }
 
-    fn get_height(&self) -> usize {
-        self.map.len() / self.width
+    let mut path = vec![Point::new(0, random.range(0..height) as isize)];
+    for x in 0..width {
+        let target = random.range(0..height) as isize;
+        while path.last().unwrap().y != target {
+            let last = *path.last().unwrap();
+            path.push(last.step(if target > last.y { Direction::Down } else { Direction::Up }));
+        }
+        if x + 1 < width {
+            path.push(path.last().unwrap().step(Direction::Right));
+        }
     }
 
-    fn get_weights(&self, value: char) -> Vec<usize> {
-        self.map.iter().filter(|pos| pos.value == value).map(|pos| pos.weight).collect()
+    let mut climbs = (2..path.len() - 1).collect::<Vec<usize>>();
+    random.shuffle(&mut climbs);
+    climbs.truncate(25);
+    let mut elevation = b'a';
+    for (index, position) in path.iter().enumerate() {
+        if climbs.contains(&index) {
+            elevation += 1;
+        }
+        *map.get_mut(*position).unwrap() = char::from(elevation);
     }
+    *map.get_mut(path[0]).unwrap() = 'S';
+    *map.get_mut(path[path.len() - 1]).unwrap() = 'E';
+
+    map.to_string()
 }
 
 #[cfg(test)]
 mod tests {
     use crate::day_12::Map;
+    use crate::error::Error;
+    use crate::geometry::Point;
 
     #[test]
     fn shortest_path_steps() {
-        let input = include_str!("resources/test/day_12_height_map_example.txt");
-        let mut map = Map::new(input, 'S', true);
-        map.update_all_directions();
-        map.update_all_weights();
-        assert_eq!(*map.get_weights('E').first().unwrap(), 31);
+        let map = Map::new(include_str!("resources/test/day_12_height_map_example.txt")).unwrap();
+        let search = map.climb(map.positions_of('S'));
+        assert_eq!(search.goal_distance(), Some(31));
+        assert_eq!(search.path(search.goal().unwrap()).unwrap().len(), 32);
     }
 
     #[test]
     fn lowest_point_to_end_shortest_path() {
-        let input = include_str!("resources/test/day_12_height_map_example.txt");
-        let mut map = Map::new(input, 'E', false);
-        map.update_all_directions();
-        map.update_all_weights();
-        assert_eq!(*map.get_weights('a').iter().min().unwrap(), 29);
+        let map = Map::new(include_str!("resources/test/day_12_height_map_example.txt")).unwrap();
+        assert_eq!(map.climb(map.positions_of('a')).goal_distance(), Some(29));
+    }
+
+    #[test]
+    fn distance_field() {
+        let map = Map::new(include_str!("resources/test/day_12_height_map_example.txt")).unwrap();
+        let field = map.distance_field();
+        assert_eq!(field[Point::ORIGIN], Some(0.0));
+        assert_eq!(field.iter().filter(|(_, distance)| distance.is_none()).count(), 0);
+        assert!(field.iter().any(|(_, distance)| *distance == Some(1.0)));
+    }
+
+    #[test]
+    fn map_without_end() {
+        assert_eq!(Map::new("Sab\nabc").err(), Some(Error::parse(12, 1, "Sab", "Height map has no E position")));
     }
 }
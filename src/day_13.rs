@@ -1,32 +1,91 @@
 use std::cmp::Ordering;
+use std::fmt::{self, Display, Formatter};
+use crate::error::{Error, Result};
+use crate::explain;
+use crate::log;
+use crate::parse::{self, Parsed, Parser};
+use crate::random::Random;
+use crate::solution::Solution;
 
-pub fn main() {
-    let input = include_str!("resources/day_13_packets.txt");
-    let size = input
-        .split("\n\n")
-        .map(|pair| pair.split_once("\n").unwrap())
-        .enumerate()
-        .map(|(index, (a, b))| { (index + 1, Packet::parse(a.trim()).cmp(&Packet::parse(b.trim()))) })
-        .fold(0, |acc, (index, status)| acc + if status == Status::Ok { index } else { 0 });
-    println!("Sum of OK indices is {}", size);
-
-    let divider_1 = "[[2]]";
-    let divider_2 = "[[6]]";
-    let mut list = (input.to_string() + "\n" + divider_1 + "\n" + divider_2)
-        .split("\n")
-        .filter(|line| !line.is_empty())
-        .map(|line| Packet::parse(line))
-        .collect::<Vec<Packet>>();
-
-    list.sort_by(|a, b| if a.cmp(b) == Status::Ok { Ordering::Less } else { Ordering::Greater });
-    let divider_1_pos = list.iter().position(|packet| *packet == Packet::parse(divider_1)).unwrap() + 1;
-    let divider_2_pos = list.iter().position(|packet| *packet == Packet::parse(divider_2)).unwrap() + 1;
-    println!("Indices of divisors multiplied is equal to {}", divider_1_pos*divider_2_pos);
+/// Day 13: Distress Signal, ordering of nested packets
+pub struct Day13;
+
+impl Solution for Day13 {
+    type Input = Vec<Packet>;
+    type Params = ();
+
+    fn name(&self) -> &'static str {
+        "Distress Signal"
+    }
+
+    fn day(&self) -> usize {
+        13
+    }
+
+    fn input(&self) -> &'static str {
+        "day_13_packets.txt"
+    }
+
+    fn parse(&self, input: &str) -> Result<Vec<Packet>> {
+        let mut packets = vec![];
+        for (line, block) in parse::blocks(input) {
+            let lines = block.lines().collect::<Vec<&str>>();
+            if lines.len() != 2 {
+                return Err(Error::parse(13, line, lines[0], "Pairs have to be two packets separated from other pairs by an empty line"));
+            }
+            for (offset, packet) in lines.iter().enumerate() {
+                packets.push(Packet::parse(packet.trim()).map_err(|err| err.at_line(line + offset))?);
+            }
+        }
+        Ok(packets)
+    }
+
+    fn part1(&self, packets: &Vec<Packet>, _params: &()) -> Result<String> {
+        let in_order = packets
+            .chunks(2)
+            .enumerate()
+            .filter(|(index, pair)| {
+                let status = pair[0].compare(&pair[1]);
+                log::trace(format_args!("pair {}: {} vs {} is {}", index + 1, pair[0], pair[1], status));
+                status == Status::Ok
+            })
+            .map(|(index, _)| index + 1)
+            .collect::<Vec<usize>>();
+        if explain::enabled() {
+            let indices = in_order.iter().map(|index| index.to_string()).collect::<Vec<String>>();
+            explain::note("pairs in order", format!("{} of {}: {}", in_order.len(), packets.len() / 2, indices.join(", ")));
+        }
+        Ok(in_order.iter().sum::<usize>().to_string())
+    }
+
+    fn part2(&self, packets: &Vec<Packet>, _params: &()) -> Result<String> {
+        let divider_1 = Packet::parse("[[2]]")?;
+        let divider_2 = Packet::parse("[[6]]")?;
+        let mut list = packets.clone();
+        list.push(divider_1.clone());
+        list.push(divider_2.clone());
+
+        list.sort_by(|a, b| match a.compare(b) {
+            Status::Ok => Ordering::Less,
+            Status::Continue => Ordering::Equal,
+            Status::Wrong => Ordering::Greater,
+        });
+        let divider_1_pos = list.iter().position(|packet| *packet == divider_1).unwrap() + 1;
+        let divider_2_pos = list.iter().position(|packet| *packet == divider_2).unwrap() + 1;
+        if explain::enabled() {
+            explain::note("divider positions", format!("{} at {}, {} at {}", divider_1, divider_1_pos, divider_2, divider_2_pos));
+        }
+        Ok((divider_1_pos * divider_2_pos).to_string())
+    }
+
+    fn generate(&self, random: &mut Random, size: usize) -> String {
+        generate(random, size)
+    }
 }
 
 impl PartialEq<Self> for Packet {
     fn eq(&self, other: &Self) -> bool {
-        return if self.is_number() && other.is_number() {
+        if self.is_number() && other.is_number() {
             self.number.unwrap() == other.number.unwrap()
         } else if self.is_list() && other.is_list() {
             self.list.as_ref().unwrap() == other.list.as_ref().unwrap()
@@ -36,9 +95,13 @@ impl PartialEq<Self> for Packet {
     }
 }
 
-enum Status {
+/// Result of comparing two packets with [Packet::compare]
+pub enum Status {
+    /// Left packet is smaller, the pair is in the right order
     Ok,
+    /// Packets are equal so far, comparison continues with the next value
     Continue,
+    /// Left packet is bigger, the pair is in the wrong order
     Wrong,
 }
 
@@ -48,22 +111,36 @@ impl PartialEq for Status {
     }
 }
 
-impl ToString for Status {
-    fn to_string(&self) -> String {
+impl Display for Status {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
-            Status::Ok => "Ok".to_string(),
-            Status::Wrong => "Wrong".to_string(),
-            Status::Continue => "Continue".to_string(),
+            Status::Ok => write!(f, "Ok"),
+            Status::Wrong => write!(f, "Wrong"),
+            Status::Continue => write!(f, "Continue"),
         }
     }
 }
 
+/// Packet of the distress signal, either a number or a list of packets
 #[derive(Clone)]
-struct Packet {
+pub struct Packet {
     number: Option<usize>,
     list: Option<Vec<Packet>>,
 }
 
+/// Packet in the notation of the puzzle input, e.g. `[1,[2,3]]`
+impl Display for Packet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match (&self.number, &self.list) {
+            (Some(number), _) => write!(f, "{}", number),
+            (None, list) => {
+                let packets = list.iter().flatten().map(Packet::to_string).collect::<Vec<String>>();
+                write!(f, "[{}]", packets.join(","))
+            }
+        }
+    }
+}
+
 impl Packet {
     fn from_number(number: usize) -> Packet {
         Packet { number: Some(number), list: None }
@@ -73,34 +150,25 @@ impl Packet {
         Packet { number: None, list: Some(list) }
     }
 
-    fn parse(input: &str) -> Packet {
-        let mut result: Vec<Packet> = vec![];
-
-        let mut strip = input.strip_prefix("[").unwrap().strip_suffix("]").unwrap();
-        while !strip.is_empty() {
-            let mut first_part = "";
-            strip = strip.strip_prefix(",").or(Some(strip)).unwrap();
-            if strip.starts_with("[") {
-                let mut num = 0;
-                for (i, c) in strip.chars().enumerate() {
-                    num += if c == '[' { 1 } else if c == ']' { -1 } else { 0 };
-                    if num == 0 {
-                        (first_part, strip) = strip.split_at(i + 1);
-                        break;
-                    }
-                }
-            } else {
-                (first_part, strip) = strip.split_once(",").or(Some((strip, ""))).unwrap()
+    /// Parse a single packet, errors are reported on line 1 as the packet doesn't know its line
+    pub fn parse(input: &str) -> Result<Packet> {
+        parse::line(13, 1, input, |parser| {
+            if parser.peek() != Some('[') {
+                return parser.fail("Packet has to be a list enclosed in brackets");
             }
+            Packet::parse_value(parser)
+        })
+    }
 
-            if first_part.starts_with("[") {
-                result.push(Packet::parse(first_part));
-            } else {
-                result.push(Packet::from_number(first_part.parse().unwrap()));
-            }
+    fn parse_value(parser: &mut Parser) -> Parsed<Packet> {
+        if parser.peek() == Some('[') {
+            parser.bracketed("[", ",", "]", Packet::parse_value).map(Packet::from_list)
+        } else {
+            parser
+                .int()
+                .map(Packet::from_number)
+                .map_err(|failure| failure.describe("Packet values have to be numbers or lists"))
         }
-
-        Packet::from_list(result)
     }
 
     fn is_number(&self) -> bool {
@@ -111,26 +179,14 @@ impl Packet {
         self.list.is_some()
     }
 
-    #[allow(dead_code)]
-    fn print(&self) {
-        if self.is_number() {
-            print!(" {} ", self.number.unwrap());
-        } else if self.is_list() {
-            print!("[");
-            self.list.as_ref().unwrap().iter().for_each(|packet| packet.print());
-            print!("]");
-        } else {
-            println!("Didnt recognize packet, empty!");
-        }
-    }
-
-    fn cmp(&self, other: &Self) -> Status {
+    /// Compare two packets, a number compared to a list is treated as a list with that number
+    pub fn compare(&self, other: &Self) -> Status {
         if self.is_list() && other.is_list() {
             for (index, packet) in self.list.as_ref().unwrap().iter().enumerate() {
                 if other.list.as_ref().unwrap().len() < index + 1 {
                     return Status::Wrong;
                 }
-                match packet.cmp(other.list.as_ref().unwrap().get(index).unwrap()) {
+                match packet.compare(other.list.as_ref().unwrap().get(index).unwrap()) {
                     Status::Ok => { return Status::Ok; }
                     Status::Wrong => { return Status::Wrong; }
                     Status::Continue => continue
@@ -141,29 +197,47 @@ impl Packet {
                 return Status::Ok;
             }
 
-            return Status::Continue;
+            Status::Continue
         } else if self.is_number() && other.is_number() {
-            return if self.number.unwrap() < other.number.unwrap() {
+            if self.number.unwrap() < other.number.unwrap() {
                 Status::Ok
             } else if self.number.unwrap() > other.number.unwrap() {
                 Status::Wrong
             } else {
                 Status::Continue
-            };
+            }
         } else {
-            return if self.is_number() {
-                Packet::from_list(vec![self.clone()]).cmp(other)
+            if self.is_number() {
+                Packet::from_list(vec![self.clone()]).compare(other)
             } else {
-                self.cmp(&Packet::from_list(vec![other.clone()]))
-            };
+                self.compare(&Packet::from_list(vec![other.clone()]))
+            }
         }
     }
 }
 
+/// Random `size` pairs of packets nested at most five lists deep
+pub fn generate(random: &mut Random, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| format!("{}\n{}\n", random_packet(random, 0), random_packet(random, 0)))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn random_packet(random: &mut Random, depth: usize) -> String {
+    let items = (0..random.range(0..5))
+        .map(|_| if depth < 4 && random.chance(1, 3) { random_packet(random, depth + 1) } else { random.range(0..11).to_string() })
+        .collect::<Vec<String>>();
+    format!("[{}]", items.join(","))
+}
+
 #[cfg(test)]
 mod tests {
     use std::cmp::Ordering;
-    use crate::day_13::{Packet, Status};
+    use crate::day_13::{Day13, Packet, Status};
+    use crate::error::Error;
+    use crate::explain::{self, Note};
+    use crate::solution::Solution;
 
     #[test]
     fn sum_of_ok_indices() {
@@ -171,7 +245,7 @@ mod tests {
             .split("\n\n")
             .map(|pair| pair.split_once("\n").unwrap())
             .enumerate()
-            .map(|(index, (a, b))| { (index + 1, Packet::parse(a.trim()).cmp(&Packet::parse(b.trim()))) })
+            .map(|(index, (a, b))| { (index + 1, Packet::parse(a.trim()).unwrap().compare(&Packet::parse(b.trim()).unwrap())) })
             .fold(0, |acc, (index, status)| acc + if status == Status::Ok { index } else { 0 });
         assert_eq!(size, 13);
     }
@@ -183,12 +257,38 @@ mod tests {
         let mut list = (include_str!("resources/test/day_13_packets_example.txt").to_string() + "\n" + divider_1 + "\n" + divider_2)
             .split("\n")
             .filter(|line| !line.is_empty())
-            .map(|line| Packet::parse(line))
+            .map(|line| Packet::parse(line).unwrap())
             .collect::<Vec<Packet>>();
 
-        list.sort_by(|a, b| if a.cmp(b) == Status::Ok { Ordering::Less } else { Ordering::Greater });
-        let divider_1_pos = list.iter().position(|packet| *packet == Packet::parse(divider_1)).unwrap() + 1;
-        let divider_2_pos = list.iter().position(|packet| *packet == Packet::parse(divider_2)).unwrap() + 1;
+        list.sort_by(|a, b| if a.compare(b) == Status::Ok { Ordering::Less } else { Ordering::Greater });
+        let divider_1_pos = list.iter().position(|packet| *packet == Packet::parse(divider_1).unwrap()).unwrap() + 1;
+        let divider_2_pos = list.iter().position(|packet| *packet == Packet::parse(divider_2).unwrap()).unwrap() + 1;
         assert_eq!(divider_1_pos*divider_2_pos, 140);
     }
+
+    #[test]
+    fn display_packet() {
+        assert_eq!(Packet::parse("[1,[2,[]],10]").unwrap().to_string(), "[1,[2,[]],10]");
+    }
+
+    #[test]
+    fn unbalanced_packet() {
+        assert_eq!(Day13.parse("[1,[2]\n[[3]]\n\n[1]\n[[2,x]]").err(), Some(Error::parse(13, 1, "[1,[2]", "Unbalanced brackets").at_column(7)));
+        assert_eq!(Day13.parse("[1]\n[[2,x]]").err(), Some(Error::parse(13, 2, "[[2,x]]", "Packet values have to be numbers or lists").at_column(5)));
+    }
+
+    #[test]
+    fn packets_not_in_pairs() {
+        let message = "Pairs have to be two packets separated from other pairs by an empty line";
+        assert_eq!(Day13.parse("[1]\n\n[2]\n[3]\n\n[4]\n").err(), Some(Error::parse(13, 1, "[1]", message)));
+        assert_eq!(Day13.parse("[1]\n[2]\n\n[3]\n[4]\n[5]\n").err(), Some(Error::parse(13, 4, "[3]", message)));
+    }
+
+    #[test]
+    fn explain_pairs_in_order() {
+        let packets = Day13.parse(include_str!("resources/test/day_13_packets_example.txt")).unwrap();
+        let (answer, notes) = explain::record(|| Day13.part1(&packets, &()));
+        assert_eq!(answer.unwrap(), "13");
+        assert_eq!(notes, vec![Note { key: "pairs in order".to_string(), value: "4 of 8: 1, 2, 4, 6".to_string() }]);
+    }
 }
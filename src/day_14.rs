@@ -1,112 +1,304 @@
-use std::collections::HashSet;
-
-pub fn main() {
-    let input = include_str!("resources/day_14.txt");
-    let mut cave = Cave::new(input);
-
-    let mut counter_touched_floor = 0;
-    let mut counter_sand_reached_entrypoint = 0;
-    let mut increment = 1;
-
-    loop {
-        let (r, d) = cave.tick();
-        counter_touched_floor += increment;
-        counter_sand_reached_entrypoint += 1;
-        if d == cave.lowest_point + 1 {
-            increment = 0;
-        }
-        if r == 500 && d == 0 {
-            break;
-        }
+use crate::checkpoint::{self, Checkpoint};
+use crate::error::{Error, Result};
+use crate::geometry::{Direction, Point};
+use crate::grid::SparseGrid;
+use crate::image::{self, Picture};
+use crate::log;
+use crate::params::Params;
+use crate::parse::{self, Parser};
+use crate::random::Random;
+use crate::simulation::{Drawable, Driver, Simulation, Traceable};
+use crate::solution::Solution;
+use crate::trace::Trace;
+use crate::visualize::{Frame, Visualizer};
+
+/// Day 14: Regolith Reservoir, sand falling into a cave
+pub struct Day14;
+
+impl Solution for Day14 {
+    type Input = Cave;
+    type Params = Day14Params;
+
+    fn name(&self) -> &'static str {
+        "Regolith Reservoir"
+    }
+
+    fn day(&self) -> usize {
+        14
+    }
+
+    fn input(&self) -> &'static str {
+        "day_14.txt"
+    }
+
+    fn parse(&self, input: &str) -> Result<Cave> {
+        Cave::new(input)
+    }
+
+    fn part1(&self, cave: &Cave, params: &Day14Params) -> Result<String> {
+        let mut cave = cave.with_entrypoint(params.entry);
+        let label = format!("day 14, sand from {} until it falls past the rocks", params.entry);
+        let fell = |cave: &Cave| cave.sand.last().is_some_and(|sand| sand.y == cave.get_floor_level());
+        checkpoint::run_until(&mut cave, &label, fell)?;
+        log::debug(format_args!("cave after the sand fell past the rocks\n{}", cave.obstacles));
+        // the last unit fell past all rocks, so it doesn't count
+        Ok((cave.sand.len() - usize::from(fell(&cave))).to_string())
+    }
+
+    fn part2(&self, cave: &Cave, params: &Day14Params) -> Result<String> {
+        let mut cave = cave.with_entrypoint(params.entry);
+        let label = format!("day 14, sand from {} until the entry point is blocked", params.entry);
+        checkpoint::run_until(&mut cave, &label, |_| false)?;
+        Ok(cave.sand.len().to_string())
+    }
+
+    fn generate(&self, random: &mut Random, size: usize) -> String {
+        generate(random, size)
+    }
+
+    /// Sand pile once it blocks the entry point, with rocks brighter than sand
+    fn export(&self, cave: &Cave, params: &Day14Params) -> Option<Picture> {
+        let mut cave = cave.with_entrypoint(params.entry);
+        cave.run_until(|_| false).ok()?;
+        Some(image::from_sparse(&cave.obstacles, |cell| if *cell == '#' { 1.0 } else { 0.4 }))
+    }
+
+    fn visualize(&self, cave: &Cave, params: &Day14Params) -> Option<Box<dyn Visualizer>> {
+        Some(Box::new(Driver::new(cave.with_entrypoint(params.entry))))
+    }
+
+    /// Every unit of sand settling until the entry point is blocked
+    fn trace(&self, cave: &Cave, params: &Day14Params) -> Result<Option<Trace>> {
+        Trace::record(&mut cave.with_entrypoint(params.entry), usize::MAX).map(Some)
+    }
+}
+
+pub struct Day14Params {
+    /// Point the sand pours into the cave from
+    pub entry: Point,
+}
+
+impl Default for Day14Params {
+    fn default() -> Day14Params {
+        Day14Params { entry: Point::new(500, 0) }
     }
-    println!("{} units of sand before it starting free falling", counter_touched_floor - 1);
-    println!("{} units of sand to start blocking sand entrypoint", counter_sand_reached_entrypoint);
 }
 
+impl Params for Day14Params {
+    fn set(&mut self, key: &str, value: &str) -> std::result::Result<(), String> {
+        match key {
+            "entry" => self.entry = Point::parse(value).ok_or_else(|| format!("parameter 'entry' has to be a point like '500,0', not '{}'", value))?,
+            _ => return Err(format!("unknown parameter '{}', use entry", key)),
+        }
+        Ok(())
+    }
+}
 
-struct Cave {
-    obstacles: HashSet<(usize, usize)>,
-    sand_entrypoint: (usize, usize),
-    lowest_point: usize,
+/// Cave scan with rocks (`#`) and settled sand (`o`), `y` is the depth below the sand entry point
+#[derive(Clone)]
+pub struct Cave {
+    obstacles: SparseGrid<char>,
+    sand_entrypoint: Point,
+    lowest_point: isize,
+    /// Settled units of sand in the order they fell
+    sand: Vec<Point>,
 }
 
 impl Cave {
-    fn new(input: &str) -> Cave {
-        let mut obstacles = HashSet::new();
-        for line in input.lines() {
-            let points = line.split(" -> ").collect::<Vec<&str>>();
-            for point_index in 0..(points.len() - 1) {
-                let (r1, d1) = points.get(point_index).unwrap().split_once(",").map(|(r, d)| (r.parse::<usize>().unwrap(), d.parse::<usize>().unwrap())).unwrap();
-                let (r2, d2) = points.get(point_index + 1).unwrap().split_once(",").map(|(r, d)| (r.parse::<usize>().unwrap(), d.parse::<usize>().unwrap())).unwrap();
-                for r in r1.min(r2)..=r1.max(r2) {
-                    for d in d1.min(d2)..=d1.max(d2) {
-                        obstacles.insert((r.clone(), d.clone()));
-                    }
+    /// Parse rock paths, one path per line in the form of `498,4 -> 498,6 -> 496,6`
+    pub fn new(input: &str) -> Result<Cave> {
+        let paths = parse::lines(14, 1, input, |parser| {
+            parser
+                .separated(" -> ", Parser::point)
+                .map_err(|failure| failure.describe("Expected a rock path in the form of '498,4 -> 498,6'"))
+        })?;
+
+        let mut obstacles = SparseGrid::new();
+        for ((index, line), points) in input.lines().enumerate().zip(paths) {
+            for segment in points.windows(2) {
+                let (mut point, end) = (segment[0], segment[1]);
+                if point.x != end.x && point.y != end.y {
+                    return Err(Error::parse(14, index + 1, line, "Rock paths have to be horizontal or vertical"));
+                }
+                obstacles.insert(point, '#');
+                while point != end {
+                    point += (end - point).signum();
+                    obstacles.insert(point, '#');
                 }
             }
         }
 
-        let mut cave = Cave {
-            obstacles,
-            sand_entrypoint: (500, 0),
-            lowest_point: 0,
+        let Some((_, bottom_right)) = obstacles.bounds() else {
+            return Err(Error::parse(14, 1, input.lines().next().unwrap_or(""), "Cave has no rocks"));
         };
 
-        cave.cache_lowest_rock_point();
+        Ok(Cave {
+            obstacles,
+            sand_entrypoint: Point::new(500, 0),
+            lowest_point: bottom_right.y,
+            sand: vec![],
+        })
+    }
 
+    /// Copy of the cave without any sand, which pours from the given point instead
+    pub fn with_entrypoint(&self, entry: Point) -> Cave {
+        let mut cave = self.clone();
+        cave.restore(0);
+        cave.sand_entrypoint = entry;
         cave
     }
 
-    fn is_obstacle(&self, x: usize, y: usize) -> bool {
-        self.obstacles.contains(&(x, y))
+    /// Depth of the lowest rock, any sand below it falls into the abyss (or onto the floor)
+    pub fn get_lowest_point(&self) -> isize {
+        self.lowest_point
+    }
+
+    fn get_floor_level(&self) -> isize {
+        self.lowest_point + 1
+    }
+
+    /// Let one unit of sand fall until it rests, on the floor at the latest, and return where it ended
+    pub fn tick(&mut self) -> Point {
+        let mut sand = self.sand_entrypoint;
+        while sand.y < self.get_floor_level() {
+            let next = [Direction::Down, Direction::DownLeft, Direction::DownRight]
+                .into_iter()
+                .map(|direction| sand.step(direction))
+                .find(|next| !self.obstacles.contains(*next));
+            match next {
+                Some(next) => sand = next,
+                None => break,
+            }
+        }
+        self.obstacles.insert(sand, 'o');
+        self.sand.push(sand);
+        sand
     }
+}
 
-    fn cache_lowest_rock_point(&mut self) {
-        let mut o = self.obstacles.clone().into_iter().collect::<Vec<(usize, usize)>>();
-        o.sort_by(|(_, a), (_, b)| b.cmp(a));
+impl Simulation for Cave {
+    /// Number of settled units, later ones are removed on restore
+    type State = usize;
 
-        self.lowest_point = o.first().unwrap().1
+    /// Let one unit fall, the simulation is over once sand blocks the entry point
+    fn step(&mut self) -> Result<bool> {
+        if self.obstacles.contains(self.sand_entrypoint) {
+            return Ok(false);
+        }
+        self.tick();
+        Ok(true)
     }
 
-    fn get_floor_level(&self) -> usize {
-        self.lowest_point + 1
+    fn snapshot(&self) -> usize {
+        self.sand.len()
     }
 
-    fn tick(&mut self) -> (usize, usize) {
-        let (mut r, mut d) = self.sand_entrypoint.clone();
-        loop {
-            if self.get_floor_level() <= d {
-                d = self.get_floor_level();
-                break;
-            } else if !self.is_obstacle(r, d + 1) {
-                d += 1;
-            } else if !self.is_obstacle(r - 1, d + 1) {
-                r -= 1;
-                d += 1;
-            } else if !self.is_obstacle(r + 1, d + 1) {
-                r += 1;
-                d += 1;
-            } else {
-                break;
-            }
+    fn restore(&mut self, units: usize) {
+        for sand in self.sand.drain(units.min(self.sand.len())..) {
+            self.obstacles.remove(sand);
+        }
+    }
+}
+
+/// Rocks and the settled sand in the order it fell, each on its own line
+impl Checkpoint for Cave {
+    fn save(&self) -> String {
+        let mut rocks = self.obstacles.iter().filter(|(_, cell)| **cell == '#').map(|(rock, _)| rock).collect::<Vec<Point>>();
+        rocks.sort_by_key(|rock| (rock.y, rock.x));
+        let points = |points: &[Point]| points.iter().map(Point::to_string).collect::<Vec<String>>().join(" ");
+        format!("rocks {}\nsand {}\n", points(&rocks), points(&self.sand))
+    }
+
+    fn load(&mut self, state: &str) -> Option<()> {
+        let points = |line: &str, prefix: &str| {
+            let points = line.strip_prefix(prefix)?;
+            points.split_whitespace().map(Point::parse).collect::<Option<Vec<Point>>>()
+        };
+        let mut lines = state.lines();
+        let rocks = points(lines.next()?, "rocks")?;
+        let sand = points(lines.next()?, "sand")?;
+        let count = self.obstacles.iter().filter(|(_, cell)| **cell == '#').count();
+        let is_rock = |point: &Point| self.obstacles.get(*point) == Some(&'#');
+        if rocks.len() != count || !rocks.iter().all(is_rock) || sand.iter().any(is_rock) {
+            return None;
+        }
+        self.restore(0);
+        for unit in sand {
+            self.obstacles.insert(unit, 'o');
+            self.sand.push(unit);
+        }
+        Some(())
+    }
+}
+
+impl Traceable for Cave {
+    fn trace(&self) -> String {
+        match self.sand.last() {
+            Some(sand) => format!("sand {} | settled at {}", self.sand.len(), sand),
+            None => "sand 0".to_string(),
         }
-        self.obstacles.insert((r.clone(), d.clone()));
-        (r, d)
     }
 }
 
+impl Drawable for Cave {
+    /// Rocks as `#`, sand as `o` with the last unit highlighted, the entry point as `+` and the floor as `=`
+    fn frame(&self) -> Frame {
+        let mut cells = self.obstacles.clone();
+        if cells.get(self.sand_entrypoint).is_none() {
+            cells.insert(self.sand_entrypoint, '+');
+        }
+        let (min, max) = cells.bounds().unwrap();
+        for x in min.x - 1..=max.x + 1 {
+            cells.insert(Point::new(x, self.get_floor_level() + 1), '=');
+        }
+        Frame {
+            cells,
+            highlighted: self.sand.last().copied().into_iter().collect(),
+            focus: self.sand.last().copied().unwrap_or(self.sand_entrypoint),
+            caption: format!("{} units of sand", self.sand.len()),
+        }
+    }
+}
+
+/// Random cave scan with `size` rock paths
+///
+/// Rocks are at least 25 below and at most 20 to the side of the sand entry point, which makes
+/// them too narrow to hold a pile of sand reaching the entry point, so sand always ends up
+/// falling into the abyss in the first part.
+pub fn generate(random: &mut Random, size: usize) -> String {
+    let depth = (size / 2 + 10).min(400);
+    (0..size.max(1))
+        .map(|_| {
+            let (mut r, mut d) = (random.range(480..521), random.range(25..25 + depth));
+            let mut points = vec![format!("{},{}", r, d)];
+            for segment in 0..random.range(1..5) {
+                if segment % 2 == 0 {
+                    r = random.range(480..521);
+                } else {
+                    d = random.range(25..25 + depth);
+                }
+                points.push(format!("{},{}", r, d));
+            }
+            points.join(" -> ") + "\n"
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::checkpoint::Checkpoint;
     use crate::day_14::Cave;
+    use crate::error::Error;
+    use crate::simulation::Simulation;
 
     #[test]
     fn units_before_free_falling() {
-        let mut cave = Cave::new(include_str!("resources/test/day_14_example.txt"));
+        let mut cave = Cave::new(include_str!("resources/test/day_14_example.txt")).unwrap();
         let mut counter = 0;
         loop {
-            let (_, d) = cave.tick();
+            let sand = cave.tick();
             counter += 1;
-            if d == cave.lowest_point + 1 {
+            if sand.y == cave.lowest_point + 1 {
                 break;
             }
         }
@@ -115,15 +307,48 @@ mod tests {
 
     #[test]
     fn units_to_reach_entrypoint() {
-        let mut cave = Cave::new(include_str!("resources/test/day_14_example.txt"));
+        let mut cave = Cave::new(include_str!("resources/test/day_14_example.txt")).unwrap();
         let mut counter = 0;
         loop {
-            let (r, d) = cave.tick();
+            let sand = cave.tick();
             counter += 1;
-            if r == 500 && d == 0 {
+            if sand.x == 500 && sand.y == 0 {
                 break;
             }
         }
         assert_eq!(counter, 93);
     }
+
+    #[test]
+    fn rewind_sand() {
+        let mut cave = Cave::new(include_str!("resources/test/day_14_example.txt")).unwrap();
+        cave.run_until(|cave| cave.sand.len() == 10).unwrap();
+        let snapshot = cave.snapshot();
+        let obstacles = cave.obstacles.len();
+        assert_eq!(cave.run_until(|_| false), Ok(83));
+        cave.restore(snapshot);
+        assert_eq!((cave.sand.len(), cave.obstacles.len()), (10, obstacles));
+    }
+
+    #[test]
+    fn resume_from_checkpoint() {
+        let input = include_str!("resources/test/day_14_example.txt");
+        let mut cave = Cave::new(input).unwrap();
+        cave.run_until(|cave| cave.sand.len() == 40).unwrap();
+        let mut resumed = Cave::new(input).unwrap();
+        assert_eq!(resumed.load(&cave.save()), Some(()));
+        assert_eq!(resumed.run_until(|_| false), Ok(53));
+        assert_eq!(resumed.sand.len(), 93);
+
+        let other = Cave::new("498,4 -> 498,6").unwrap();
+        assert_eq!(Cave::new(input).unwrap().load(&other.save()), None);
+    }
+
+    #[test]
+    fn diagonal_rock_path() {
+        assert_eq!(
+            Cave::new("498,4 -> 498,6\n503,4 -> 502,5").err(),
+            Some(Error::parse(14, 2, "503,4 -> 502,5", "Rock paths have to be horizontal or vertical"))
+        );
+    }
 }
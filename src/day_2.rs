@@ -1,50 +1,99 @@
-#[allow(dead_code)]
-pub fn main() {
-    let game_str = include_str!("resources/day_2_rock_paper_scissors.txt");
-    println!("Incorrect strategy: {}", calculate_score(game_str, false));
-    println!("Correct strategy: {}", calculate_score(game_str, true));
+use crate::error::{Error, Result};
+use crate::random::Random;
+use crate::solution::Solution;
+
+/// Day 2: Rock Paper Scissors, score of the strategy guide
+pub struct Day2;
+
+impl Solution for Day2 {
+    type Input = Vec<(RpsGame, RpsGame)>;
+    type Params = ();
+
+    fn name(&self) -> &'static str {
+        "Rock Paper Scissors"
+    }
+
+    fn day(&self) -> usize {
+        2
+    }
+
+    fn input(&self) -> &'static str {
+        "day_2_rock_paper_scissors.txt"
+    }
+
+    fn parse(&self, game_str: &str) -> Result<Vec<(RpsGame, RpsGame)>> {
+        parse_rounds(game_str)
+    }
+
+    fn part1(&self, rounds: &Vec<(RpsGame, RpsGame)>, _params: &()) -> Result<String> {
+        Ok(calculate_score(rounds, false).to_string())
+    }
+
+    fn part2(&self, rounds: &Vec<(RpsGame, RpsGame)>, _params: &()) -> Result<String> {
+        Ok(calculate_score(rounds, true).to_string())
+    }
+
+    fn generate(&self, random: &mut Random, size: usize) -> String {
+        generate(random, size)
+    }
 }
 
-fn calculate_score(game_str: &str, correct_strategy: bool) -> u32 {
-    let mut score = 0;
+/// Parse both columns of every round as moves, the second column is reinterpreted later
+/// depending on the strategy
+pub fn parse_rounds(game_str: &str) -> Result<Vec<(RpsGame, RpsGame)>> {
     game_str
         .lines()
-        .map(|line| RpsGame::get_round_moves(line, correct_strategy))
-        .for_each(|(opponent, me)| score += me.play(&opponent) + me.get_shape_value());
-    return score;
+        .enumerate()
+        .map(|(index, line)| RpsGame::get_round_moves(line).ok_or_else(|| Error::parse(2, index + 1, line, "Expected a round in the form of 'A X'")))
+        .collect()
+}
+
+/// Total score of all rounds, with the correct strategy the second column is the outcome
+pub fn calculate_score(rounds: &[(RpsGame, RpsGame)], correct_strategy: bool) -> u32 {
+    let mut score = 0;
+    rounds
+        .iter()
+        .map(|(opponent, me)| (opponent, if correct_strategy { RpsGame::get_my_move_on_opponent(me, opponent) } else { *me }))
+        .for_each(|(opponent, me)| score += me.play(opponent) + me.get_shape_value());
+    score
 }
 
-enum RpsGame {
+/// Shape played in a round
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RpsGame {
     Rock,
     Paper,
     Scissors,
 }
 
 impl RpsGame {
-    fn get_move(movement: &str) -> RpsGame {
+    /// Shape for either column of the strategy guide, A/X is rock, B/Y paper and C/Z scissors
+    pub fn get_move(movement: &str) -> Option<RpsGame> {
         match movement {
-            "A" | "X" => RpsGame::Rock,
-            "B" | "Y" => RpsGame::Paper,
-            "C" | "Z" => RpsGame::Scissors,
-            _ => panic!()
+            "A" | "X" => Some(RpsGame::Rock),
+            "B" | "Y" => Some(RpsGame::Paper),
+            "C" | "Z" => Some(RpsGame::Scissors),
+            _ => None
         }
     }
 
-    fn get_my_move_on_opponent(me: &str, opponent: &str) -> RpsGame {
+    /// With the correct strategy the second column is the outcome: X (rock) means lose,
+    /// Y (paper) draw and Z (scissors) win
+    pub fn get_my_move_on_opponent(me: &RpsGame, opponent: &RpsGame) -> RpsGame {
         match me {
-            "Y" => Self::get_move(opponent),
-            "Z" => if matches!(opponent, "A") { RpsGame::Paper } else { if matches!(opponent, "B") { RpsGame::Scissors } else { RpsGame::Rock } },
-            "X" => if matches!(opponent, "A") { RpsGame::Scissors } else { if matches!(opponent, "B") { RpsGame::Rock } else { RpsGame::Paper } },
-            _ => panic!()
+            RpsGame::Paper => *opponent,
+            RpsGame::Scissors => match opponent { RpsGame::Rock => RpsGame::Paper, RpsGame::Paper => RpsGame::Scissors, RpsGame::Scissors => RpsGame::Rock },
+            RpsGame::Rock => match opponent { RpsGame::Rock => RpsGame::Scissors, RpsGame::Paper => RpsGame::Rock, RpsGame::Scissors => RpsGame::Paper },
         }
     }
 
-    fn get_round_moves(movements: &str, correct_strategy: bool) -> (RpsGame, RpsGame) {
-        let (move_1, move_2) = movements.split_once(" ").expect("Couldn't split moves");
-        (Self::get_move(move_1), if correct_strategy { Self::get_my_move_on_opponent(move_2, move_1) } else { Self::get_move(move_2) })
+    fn get_round_moves(movements: &str) -> Option<(RpsGame, RpsGame)> {
+        let (move_1, move_2) = movements.split_once(' ')?;
+        Some((Self::get_move(move_1)?, Self::get_move(move_2)?))
     }
 
-    fn play(&self, opponent: &RpsGame) -> u32 {
+    /// Outcome score of playing this shape against the opponent
+    pub fn play(&self, opponent: &RpsGame) -> u32 {
         if self.get_shape_value() == opponent.get_shape_value() {
             return 3;
         }
@@ -56,7 +105,7 @@ impl RpsGame {
         }
     }
 
-    fn get_shape_value(&self) -> u32 {
+    pub fn get_shape_value(&self) -> u32 {
         match self {
             RpsGame::Rock => 1,
             RpsGame::Paper => 2,
@@ -65,19 +114,32 @@ impl RpsGame {
     }
 }
 
+/// Random strategy guide with `size` rounds
+pub fn generate(random: &mut Random, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| format!("{} {}\n", random.choose(&['A', 'B', 'C']), random.choose(&['X', 'Y', 'Z'])))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::day_2::calculate_score;
+    use crate::day_2::{calculate_score, parse_rounds};
+    use crate::error::Error;
 
     #[test]
     fn example_game_incorrect_strategy() {
         let game_str = include_str!("resources/test/day_2_rock_paper_scissors_example.txt");
-        assert_eq!(calculate_score(game_str, false), 15)
+        assert_eq!(calculate_score(&parse_rounds(game_str).unwrap(), false), 15)
     }
 
     #[test]
     fn example_game_correct_strategy() {
         let game_str = include_str!("resources/test/day_2_rock_paper_scissors_example.txt");
-        assert_eq!(calculate_score(game_str, true), 12)
+        assert_eq!(calculate_score(&parse_rounds(game_str).unwrap(), true), 12)
+    }
+
+    #[test]
+    fn unknown_move() {
+        assert_eq!(parse_rounds("A Y\nD X"), Err(Error::parse(2, 2, "D X", "Expected a round in the form of 'A X'")));
     }
 }
@@ -1,52 +1,173 @@
-#[allow(dead_code)]
-pub fn main() {
-    let rucksacks_str = include_str!("resources/day_3_rucksacks.txt");
-    let (misplaced_sum, badges_sum) = calculate_misplaced_types_and_badges_sum(rucksacks_str);
-    println!("Sum of misplaced types priorities: {}", misplaced_sum);
-    println!("Sum of badges: {}", badges_sum);
+use crate::error::{Error, Result};
+use crate::random::Random;
+use crate::solution::Solution;
+
+/// Day 3: Rucksack Reorganization, priorities of misplaced items and badges
+pub struct Day3;
+
+impl Solution for Day3 {
+    type Input = Vec<String>;
+    type Params = ();
+
+    fn name(&self) -> &'static str {
+        "Rucksack Reorganization"
+    }
+
+    fn day(&self) -> usize {
+        3
+    }
+
+    fn input(&self) -> &'static str {
+        "day_3_rucksacks.txt"
+    }
+
+    fn parse(&self, rucksacks_str: &str) -> Result<Vec<String>> {
+        parse_rucksacks(rucksacks_str)
+    }
+
+    fn part1(&self, rucksacks: &Vec<String>, _params: &()) -> Result<String> {
+        Ok(calculate_misplaced_types_sum(rucksacks)?.to_string())
+    }
+
+    fn part2(&self, rucksacks: &Vec<String>, _params: &()) -> Result<String> {
+        Ok(calculate_badges_sum(rucksacks)?.to_string())
+    }
+
+    fn generate(&self, random: &mut Random, size: usize) -> String {
+        generate(random, size)
+    }
 }
 
-fn calculate_misplaced_types_and_badges_sum(rucksacks_str: &str) -> (usize, usize) {
-    //a..zA..Z in a list, index corresponds to priority-1
-    let types = ('a'..='z').chain('A'..='Z').collect::<Vec<char>>();
+/// Items of every rucksack, one rucksack per line in groups of three, item types have to be ASCII letters
+pub fn parse_rucksacks(rucksacks_str: &str) -> Result<Vec<String>> {
+    let mut rucksacks = vec![];
+    for (index, rucksack) in rucksacks_str.lines().enumerate() {
+        if let Some(column) = rucksack.chars().position(|item_type| !item_type.is_ascii_alphabetic()) {
+            return Err(Error::parse(3, index + 1, rucksack, "Item types have to be letters").at_column(column + 1));
+        }
+        rucksacks.push(rucksack.to_string());
+    }
+    if rucksacks.len() % 3 != 0 {
+        let last = rucksacks.last().map(String::as_str).unwrap_or("");
+        return Err(Error::parse(3, rucksacks.len(), last, "Rucksacks have to come in groups of three"));
+    }
+    Ok(rucksacks)
+}
 
-    let mut misplaced_sum = 0;
-    let mut badges_sum = 0;
-    let mut group: Vec<&str> = vec![];
+/// Priority of an item type, a..z are 1..26 and A..Z are 27..52
+fn priority(item_type: char, line: usize, rucksack: &str) -> Result<usize> {
+    match item_type {
+        'a'..='z' => Ok(item_type as usize - 'a' as usize + 1),
+        'A'..='Z' => Ok(item_type as usize - 'A' as usize + 27),
+        _ => Err(Error::parse(3, line, rucksack, "Item types have to be letters")),
+    }
+}
 
-    for rucksack in rucksacks_str.lines() {
+/// Sum of priorities of item types which are in both compartments of a rucksack
+pub fn calculate_misplaced_types_sum(rucksacks: &[String]) -> Result<usize> {
+    let mut misplaced_sum = 0;
+    for (index, rucksack) in rucksacks.iter().enumerate() {
         let (comp_1, comp_2) = rucksack.split_at(rucksack.len() / 2);
         let duplicate = comp_1
             .chars()
-            .find(|item_type| comp_2.contains(item_type.to_string().as_str()))
-            .expect("No duplicate was found");
-        misplaced_sum += types.iter().position(|&item_type| item_type == duplicate).unwrap() + 1;
-
-        group.push(rucksack);
-        if group.len() == 3 {
-            group.sort_by(|rs_1, rs_2| rs_1.len().cmp(&rs_2.len()));
-            let badge = group.first()
-                .expect("Group didn't have first element")
-                .chars()
-                .find(|item_type| group[1].contains(item_type.to_string().as_str()) && group[2].contains(item_type.to_string().as_str()))
-                .expect("No badge found!");
-            badges_sum += types.iter().position(|&item_type| item_type == badge).unwrap() + 1;
-            group = vec![];
-        }
+            .find(|item_type| comp_2.contains(*item_type))
+            .ok_or_else(|| Error::solve(3, &format!("No duplicate was found in rucksack {}", index + 1)))?;
+        misplaced_sum += priority(duplicate, index + 1, rucksack)?;
+    }
+    Ok(misplaced_sum)
+}
+
+/// Sum of priorities of badges, the only item type shared by each group of three rucksacks
+pub fn calculate_badges_sum(rucksacks: &[String]) -> Result<usize> {
+    let mut badges_sum = 0;
+    for (group_index, group) in rucksacks.chunks_exact(3).enumerate() {
+        let line = group_index * 3 + 3;
+        let mut group = group.iter().collect::<Vec<&String>>();
+        group.sort_by_key(|rucksack| rucksack.len());
+        let badge = group[0]
+            .chars()
+            .find(|item_type| group[1].contains(*item_type) && group[2].contains(*item_type))
+            .ok_or_else(|| Error::solve(3, &format!("No badge found in the group of rucksacks {} to {}", line - 2, line)))?;
+        badges_sum += priority(badge, line, &rucksacks[line - 1])?;
     }
+    Ok(badges_sum)
+}
+
+/// Sum of priorities of items in both compartments, and of badges shared by groups of three
+pub fn calculate_misplaced_types_and_badges_sum(rucksacks_str: &str) -> Result<(usize, usize)> {
+    let rucksacks = parse_rucksacks(rucksacks_str)?;
+    Ok((calculate_misplaced_types_sum(&rucksacks)?, calculate_badges_sum(&rucksacks)?))
+}
 
-    (misplaced_sum, badges_sum)
+/// Random `size` rucksacks, rounded up to whole groups of three
+///
+/// Every rucksack has exactly one item type in both compartments and every group shares exactly
+/// one badge, other item types of a group are picked from disjoint pools.
+pub fn generate(random: &mut Random, size: usize) -> String {
+    let mut rucksacks = String::new();
+    for _ in 0..size.max(1).div_ceil(3) {
+        let mut types = ('a'..='z').chain('A'..='Z').collect::<Vec<char>>();
+        random.shuffle(&mut types);
+        let (special, pool) = types.split_at(4);
+        let badge = special[0];
+        for (misplaced, pool) in special[1..].iter().zip(pool.chunks(pool.len() / 3)) {
+            let half = random.range(1..pool.len() / 2 + 1);
+            let mut comp_1 = pool[..half].to_vec();
+            let mut comp_2 = pool[half..2 * half - 1].to_vec();
+            comp_1.push(*misplaced);
+            comp_2.push(*misplaced);
+            comp_2.push(badge);
+            random.shuffle(&mut comp_1);
+            random.shuffle(&mut comp_2);
+            rucksacks.extend(comp_1.iter().chain(comp_2.iter()));
+            rucksacks.push('\n');
+        }
+    }
+    rucksacks
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::day_3::{calculate_misplaced_types_and_badges_sum};
+    use crate::day_3::calculate_misplaced_types_and_badges_sum;
+    use crate::error::Error;
 
     #[test]
     fn misplaced_types_and_badges_sum() {
         let rucksacks_str = include_str!("resources/test/day_3_rucksacks_example.txt");
-        let (misplaced_sum, badges_sum) = calculate_misplaced_types_and_badges_sum(rucksacks_str);
+        let (misplaced_sum, badges_sum) = calculate_misplaced_types_and_badges_sum(rucksacks_str).unwrap();
         assert_eq!(misplaced_sum, 157);
         assert_eq!(badges_sum, 70);
     }
+
+    #[test]
+    fn rucksack_with_other_characters() {
+        assert_eq!(
+            calculate_misplaced_types_and_badges_sum("vJrwpWtwJgWrhcsFMMfFFhFp\néa\nab"),
+            Err(Error::parse(3, 2, "éa", "Item types have to be letters").at_column(1))
+        );
+    }
+
+    #[test]
+    fn rucksack_without_duplicate() {
+        assert_eq!(
+            calculate_misplaced_types_and_badges_sum("vJrwpWtwJgWrhcsFMMfFFhFp\nabcdef\naa"),
+            Err(Error::solve(3, "No duplicate was found in rucksack 2"))
+        );
+    }
+
+    #[test]
+    fn group_without_badge() {
+        assert_eq!(
+            calculate_misplaced_types_and_badges_sum("aa\nbb\ncc"),
+            Err(Error::solve(3, "No badge found in the group of rucksacks 1 to 3"))
+        );
+    }
+
+    #[test]
+    fn incomplete_group() {
+        assert_eq!(
+            calculate_misplaced_types_and_badges_sum("vJrwpWtwJgWrhcsFMMfFFhFp\nabca"),
+            Err(Error::parse(3, 2, "abca", "Rucksacks have to come in groups of three"))
+        );
+    }
 }
@@ -1,27 +1,69 @@
-#[allow(dead_code)]
-pub fn main() {
-    let cleaning_plan_str = include_str!("resources/day_4_cleaning.txt");
-    println!("There are {} fully contained plans", count_contained(cleaning_plan_str, true));
-    println!("There are {} partially contained plans", count_contained(cleaning_plan_str, false));
+use crate::error::Result;
+use crate::parse::{self, Parsed, Parser};
+use crate::random::Random;
+use crate::solution::Solution;
+
+/// Day 4: Camp Cleanup, overlapping section assignments
+pub struct Day4;
+
+impl Solution for Day4 {
+    type Input = Vec<PairRanges>;
+    type Params = ();
+
+    fn name(&self) -> &'static str {
+        "Camp Cleanup"
+    }
+
+    fn day(&self) -> usize {
+        4
+    }
+
+    fn input(&self) -> &'static str {
+        "day_4_cleaning.txt"
+    }
+
+    fn parse(&self, cleaning_plan_str: &str) -> Result<Vec<PairRanges>> {
+        parse_cleaning_plan(cleaning_plan_str)
+    }
+
+    fn part1(&self, plan: &Vec<PairRanges>, _params: &()) -> Result<String> {
+        Ok(count_contained(plan, true).to_string())
+    }
+
+    fn part2(&self, plan: &Vec<PairRanges>, _params: &()) -> Result<String> {
+        Ok(count_contained(plan, false).to_string())
+    }
+
+    fn generate(&self, random: &mut Random, size: usize) -> String {
+        generate(random, size)
+    }
 }
 
-fn count_contained(cleaning_plan_str: &str, fully_contained: bool) -> u32 {
-    cleaning_plan_str
-        .lines()
-        .map(|pair| {
-            let list = pair.split(",").collect::<Vec<&str>>();
-            (list[0], list[1])
-        })
-        .map(|(first_elf, second_elf)| {
-            let first_split = first_elf.split("-").map(|str| str.parse::<u32>().expect("Couldn't parse")).collect::<Vec<u32>>();
-            let second_split = second_elf.split("-").map(|str| str.parse::<u32>().expect("Couldn't parse")).collect::<Vec<u32>>();
-            ((first_split[0], first_split[1]), (second_split[0], second_split[1]))
-        })
+/// Section ranges of both elves in a pair
+pub type PairRanges = ((u32, u32), (u32, u32));
+
+/// Section ranges of every pair, one pair per line
+pub fn parse_cleaning_plan(cleaning_plan_str: &str) -> Result<Vec<PairRanges>> {
+    parse::lines(4, 1, cleaning_plan_str, |parser| {
+        parser
+            .pair(parse_range, ",", parse_range)
+            .map_err(|failure| failure.describe("Expected a pair of ranges in the form of '2-4,6-8'"))
+    })
+}
+
+fn parse_range(parser: &mut Parser) -> Parsed<(u32, u32)> {
+    parser.pair(Parser::int, "-", Parser::int)
+}
+
+/// Number of pairs where one range fully contains the other, or where they overlap at all
+pub fn count_contained(plan: &[PairRanges], fully_contained: bool) -> u32 {
+    plan
+        .iter()
         .fold(0, |acc, pair_ranges| {
             if fully_contained {
-                count_fully_contained(pair_ranges, acc)
+                count_fully_contained(*pair_ranges, acc)
             } else {
-                count_partially_contained(pair_ranges, acc)
+                count_partially_contained(*pair_ranges, acc)
             }
         })
 }
@@ -54,19 +96,37 @@ fn is_between(num: u32, start: u32, end: u32) -> bool {
     false
 }
 
+/// Random cleaning plan with `size` pairs of section ranges
+pub fn generate(random: &mut Random, size: usize) -> String {
+    let mut range = || {
+        let start = random.range(1..100);
+        format!("{}-{}", start, random.range(start..100))
+    };
+    (0..size.max(1)).map(|_| format!("{},{}\n", range(), range())).collect()
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::day_4::count_contained;
+    use crate::day_4::{count_contained, parse_cleaning_plan};
+    use crate::error::Error;
 
     #[test]
     fn fully_contained() {
         let cleaning_plan_str = include_str!("resources/test/day_4_cleaning_example.txt");
-        assert_eq!(count_contained(cleaning_plan_str, true), 2);
+        assert_eq!(count_contained(&parse_cleaning_plan(cleaning_plan_str).unwrap(), true), 2);
     }
 
     #[test]
     fn partially_contained() {
         let cleaning_plan_str = include_str!("resources/test/day_4_cleaning_example.txt");
-        assert_eq!(count_contained(cleaning_plan_str, false), 4);
+        assert_eq!(count_contained(&parse_cleaning_plan(cleaning_plan_str).unwrap(), false), 4);
+    }
+
+    #[test]
+    fn malformed_range() {
+        assert_eq!(
+            parse_cleaning_plan("2-4,6-8\n2-3;4-5"),
+            Err(Error::parse(4, 2, "2-3;4-5", "Expected a pair of ranges in the form of '2-4,6-8'").at_column(4))
+        );
     }
 }
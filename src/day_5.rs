@@ -1,58 +1,109 @@
 use std::collections::HashMap;
+use crate::error::{Error, Result};
+use crate::parse::{self, Parsed, Parser};
+use crate::random::Random;
+use crate::simulation::{Simulation, Traceable};
+use crate::solution::Solution;
+use crate::trace::Trace;
 
-#[allow(dead_code)]
-pub fn main() {
-    let cranes_str = include_str!("resources/day_5_crates.txt");
-    let mut to_rearrange = parse_initial_state(cranes_str);
-    rearrange_crates(cranes_str, &mut to_rearrange, true);
-    println!("Simple pop results: {}", get_top_items(to_rearrange));
+/// Day 5: Supply Stacks, crates rearranged by a crane
+pub struct Day5;
 
-    let mut to_rearrange = parse_initial_state(cranes_str);
-    rearrange_crates(cranes_str, &mut to_rearrange, false);
-    println!("Multi pop results: {}", get_top_items(to_rearrange));
+impl Solution for Day5 {
+    type Input = (HashMap<u32, Stack<char>>, Vec<Move>);
+    type Params = ();
+
+    fn name(&self) -> &'static str {
+        "Supply Stacks"
+    }
+
+    fn day(&self) -> usize {
+        5
+    }
+
+    fn input(&self) -> &'static str {
+        "day_5_crates.txt"
+    }
+
+    fn parse(&self, cranes_str: &str) -> Result<(HashMap<u32, Stack<char>>, Vec<Move>)> {
+        Ok((parse_initial_state(cranes_str)?, parse_moves(cranes_str)?))
+    }
+
+    fn part1(&self, (initial_state, moves): &(HashMap<u32, Stack<char>>, Vec<Move>), _params: &()) -> Result<String> {
+        let mut to_rearrange = initial_state.clone();
+        rearrange_crates(moves, &mut to_rearrange, true)?;
+        Ok(get_top_items(to_rearrange))
+    }
+
+    fn part2(&self, (initial_state, moves): &(HashMap<u32, Stack<char>>, Vec<Move>), _params: &()) -> Result<String> {
+        let mut to_rearrange = initial_state.clone();
+        rearrange_crates(moves, &mut to_rearrange, false)?;
+        Ok(get_top_items(to_rearrange))
+    }
+
+    fn generate(&self, random: &mut Random, size: usize) -> String {
+        generate(random, size)
+    }
+
+    /// Stacks after every move of the crane moving crates one at a time
+    fn trace(&self, (initial_state, moves): &(HashMap<u32, Stack<char>>, Vec<Move>), _params: &()) -> Result<Option<Trace>> {
+        Trace::record(&mut Crane::new(initial_state.clone(), moves.clone(), true), usize::MAX).map(Some)
+    }
+}
+
+/// Single crane instruction, `move 1 from 2 to 1`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Move {
+    pub quantity: u32,
+    pub from: u32,
+    pub to: u32,
 }
 
-#[derive(Debug)]
-struct Stack<I> {
+/// Stack of crates, the last item is on top
+#[derive(Debug, Clone)]
+pub struct Stack<I> {
     stack: Vec<I>,
 }
 
 impl<I> Stack<I> {
-    fn new() -> Stack<I> {
+    pub fn new() -> Stack<I> {
         Stack {
             stack: Vec::new()
         }
     }
 
-    fn push(&mut self, value: I) {
+    pub fn push(&mut self, value: I) {
         self.stack.push(value);
     }
 
-    fn push_vector(&mut self, values: &mut Vec<I>) {
+    pub fn push_vector(&mut self, values: &mut Vec<I>) {
         self.stack.append(values);
     }
 
-    fn pop(&mut self) -> I {
-        if self.stack.len() >= 1 {
-            self.stack.pop().unwrap()
-        } else {
-            panic!();
-        }
+    pub fn pop(&mut self) -> Option<I> {
+        self.stack.pop()
     }
 
     /// Pop many elements from stack at once
     ///
     /// Instead of popping multiple elements one by one, this function doesn't reverse the order
-    fn pop_many(&mut self, quantity: u32) -> Vec<I> {
+    pub fn pop_many(&mut self, quantity: u32) -> Option<Vec<I>> {
+        let start = self.stack.len().checked_sub(usize::try_from(quantity).ok()?)?;
         let mut rtrn = Vec::new();
-        for item in self.stack.drain((self.stack.len() - usize::try_from(quantity).unwrap())..) {
+        for item in self.stack.drain(start..) {
             rtrn.push(item);
         }
-        return rtrn;
+        Some(rtrn)
     }
 
-    fn get_last_item(&self) -> &I {
-        self.stack.last().unwrap()
+    pub fn get_last_item(&self) -> Option<&I> {
+        self.stack.last()
+    }
+}
+
+impl<I> Default for Stack<I> {
+    fn default() -> Self {
+        Stack::new()
     }
 }
 
@@ -62,79 +113,200 @@ impl<I: PartialEq> PartialEq for Stack<I> {
     }
 }
 
-fn parse_initial_state(rearrange_plan: &str) -> HashMap<u32, Stack<char>> {
-    let (diagram, _) = rearrange_plan.split_once("\n\n").unwrap();
+/// Split the plan into the diagram and the movements together with the line number of the first movement
+fn split_plan(rearrange_plan: &str) -> Result<(&str, &str, usize)> {
+    match parse::blocks(rearrange_plan)[..] {
+        [(_, diagram), (first_line, movements)] => Ok((diagram, movements, first_line)),
+        _ => Err(Error::parse(5, 1, rearrange_plan.lines().next().unwrap_or(""), "Diagram has to be separated from movements by an empty line")),
+    }
+}
+
+/// Stacks drawn in the diagram keyed by their number
+pub fn parse_initial_state(rearrange_plan: &str) -> Result<HashMap<u32, Stack<char>>> {
+    let (diagram, _, _) = split_plan(rearrange_plan)?;
+    let line_count = diagram.lines().count();
 
     let mut initial_state: HashMap<u32, Stack<char>> = HashMap::new();
-    let mut column_positions: HashMap<u32, u32> = HashMap::new();
+    let mut column_positions: HashMap<usize, u32> = HashMap::new();
 
     for (idx, line) in diagram.lines().rev().enumerate() {
+        let line_number = line_count - idx;
         if idx == 0 {
-            line.chars().enumerate().for_each(|(pos, content)| {
+            for (pos, content) in line.chars().enumerate() {
                 if !content.is_whitespace() {
-                    let column_number = content.to_digit(10).unwrap();
+                    let column_number = content
+                        .to_digit(10)
+                        .ok_or_else(|| Error::parse(5, line_number, line, "Stack numbers have to be digits"))?;
                     initial_state.insert(column_number, Stack::new());
-                    column_positions.insert(u32::try_from(pos).unwrap(), column_number);
+                    column_positions.insert(pos, column_number);
                 }
-            });
+            }
+            if initial_state.is_empty() {
+                return Err(Error::parse(5, line_number, line, "Diagram has no stacks"));
+            }
         } else {
-            line.chars().enumerate().for_each(|(pos, content)| {
+            for (pos, content) in line.chars().enumerate() {
                 if content.is_alphabetic() {
-                    initial_state
-                        .get_mut(&column_positions[&u32::try_from(pos).unwrap()])
-                        .unwrap()
-                        .push(content);
+                    let column_number = column_positions
+                        .get(&pos)
+                        .ok_or_else(|| Error::parse(5, line_number, line, "Crate isn't above any stack number"))?;
+                    initial_state.get_mut(column_number).unwrap().push(content);
                 }
-            })
+            }
         }
     }
 
-    initial_state
+    Ok(initial_state)
 }
 
-fn rearrange_crates(rearrange_plan: &str, initial_plan: &mut HashMap<u32, Stack<char>>, simple_pop: bool) {
-    let (_, movements) = rearrange_plan.split_once("\n\n").unwrap();
+/// Crane instructions following the diagram
+pub fn parse_moves(rearrange_plan: &str) -> Result<Vec<Move>> {
+    let (_, movements, first_line) = split_plan(rearrange_plan)?;
 
-    movements.lines().for_each(|movement| {
-        let values = movement
-            .split_whitespace()
-            .filter(|word| word.parse::<u32>().is_ok())
-            .map(|num| num.parse::<u32>().unwrap())
-            .collect::<Vec<u32>>();
+    parse::lines(5, first_line, movements, |parser| {
+        parse_move(parser).map_err(|failure| failure.describe("Expected a movement in the form of 'move 1 from 2 to 1'"))
+    })
+}
 
-        if values.len() == 3 {
-            let quantity = values[0];
-            let from = values[1];
-            let to = values[2];
-            if simple_pop {
-                for _ in 0..quantity {
-                    let item = initial_plan.get_mut(&from).unwrap().pop();
-                    initial_plan.get_mut(&to).unwrap().push(item);
-                }
-            } else {
-                let mut items = initial_plan.get_mut(&from).unwrap().pop_many(quantity);
-                initial_plan.get_mut(&to).unwrap().push_vector(&mut items);
+fn parse_move(parser: &mut Parser) -> Parsed<Move> {
+    parser.literal("move ")?;
+    let quantity = parser.int()?;
+    parser.literal(" from ")?;
+    let from = parser.int()?;
+    parser.literal(" to ")?;
+    Ok(Move { quantity, from, to: parser.int()? })
+}
+
+/// Apply the moves, `simple_pop` moves crates one at a time instead of all at once
+pub fn rearrange_crates(moves: &[Move], initial_plan: &mut HashMap<u32, Stack<char>>, simple_pop: bool) -> Result<()> {
+    let mut crane = Crane::new(std::mem::take(initial_plan), moves.to_vec(), simple_pop);
+    let result = crane.run_until(|_| false);
+    *initial_plan = crane.stacks;
+    result.map(|_| ())
+}
+
+/// Crane applying one move at a time
+#[derive(Clone)]
+pub struct Crane {
+    stacks: HashMap<u32, Stack<char>>,
+    moves: Vec<Move>,
+    done: usize,
+    simple_pop: bool,
+}
+
+impl Crane {
+    pub fn new(stacks: HashMap<u32, Stack<char>>, moves: Vec<Move>, simple_pop: bool) -> Crane {
+        Crane { stacks, moves, done: 0, simple_pop }
+    }
+
+    pub fn get_stacks(&self) -> &HashMap<u32, Stack<char>> {
+        &self.stacks
+    }
+}
+
+impl Simulation for Crane {
+    type State = Crane;
+
+    fn step(&mut self) -> Result<bool> {
+        let Some(Move { quantity, from, to }) = self.moves.get(self.done) else {
+            return Ok(false);
+        };
+        let stacks = &mut self.stacks;
+        if !stacks.contains_key(to) {
+            return Err(Error::solve(5, &format!("There is no stack {} to move crates to", to)));
+        }
+        let from_stack = stacks
+            .get_mut(from)
+            .ok_or_else(|| Error::solve(5, &format!("There is no stack {} to move crates from", from)))?;
+        let not_enough = || Error::solve(5, &format!("Stack {} doesn't have {} crates to move", from, quantity));
+        if self.simple_pop {
+            for _ in 0..*quantity {
+                let item = stacks.get_mut(from).unwrap().pop().ok_or_else(not_enough)?;
+                stacks.get_mut(to).unwrap().push(item);
             }
         } else {
-            panic!();
+            let mut items = from_stack.pop_many(*quantity).ok_or_else(not_enough)?;
+            stacks.get_mut(to).unwrap().push_vector(&mut items);
         }
-    });
+        self.done += 1;
+        Ok(true)
+    }
+
+    fn snapshot(&self) -> Crane {
+        self.clone()
+    }
+
+    fn restore(&mut self, state: Crane) {
+        *self = state;
+    }
 }
 
-fn get_top_items(crates_plan: HashMap<u32, Stack<char>>) -> String {
+impl Traceable for Crane {
+    /// Stacks ordered by their number, crates listed from the bottom
+    fn trace(&self) -> String {
+        let mut numbers = self.stacks.keys().collect::<Vec<&u32>>();
+        numbers.sort();
+        let stacks = numbers.iter().map(|number| format!("{}: [{}]", number, self.stacks[number].stack.iter().collect::<String>()));
+        format!("move {} | {}", self.done, stacks.collect::<Vec<String>>().join(" "))
+    }
+}
+
+/// Crates on top of every stack, ordered by stack number
+pub fn get_top_items(crates_plan: HashMap<u32, Stack<char>>) -> String {
     let mut result = String::new();
-    let from = crates_plan.keys().min().unwrap().clone();
-    let to = crates_plan.keys().max().unwrap().clone() + 1;
+    let from = *crates_plan.keys().min().unwrap();
+    let to = *crates_plan.keys().max().unwrap() + 1;
     for idx in from..to {
-        result += crates_plan[&u32::try_from(idx).unwrap().clone()].get_last_item().to_string().as_str();
+        if let Some(item) = crates_plan.get(&idx).and_then(|stack| stack.get_last_item()) {
+            result.push(*item);
+        }
     }
     result
 }
 
+/// Random diagram of up to nine stacks followed by `size` moves
+///
+/// Moves are simulated while generating, so they never take more crates than the stack has.
+pub fn generate(random: &mut Random, size: usize) -> String {
+    let stacks = random.range(2..10);
+    let mut heights = (0..stacks).map(|_| random.range(0..8)).collect::<Vec<usize>>();
+    heights[0] = heights[0].max(1);
+
+    let mut plan = String::new();
+    for level in (0..*heights.iter().max().unwrap()).rev() {
+        let row = heights
+            .iter()
+            .map(|height| if *height > level { format!("[{}]", random.choose(&('A'..='Z').collect::<Vec<char>>())) } else { "   ".to_string() })
+            .collect::<Vec<String>>()
+            .join(" ");
+        plan += row.trim_end();
+        plan.push('\n');
+    }
+    plan += &(1..=stacks).map(|stack| format!(" {} ", stack)).collect::<Vec<String>>().join(" ");
+    plan += "\n\n";
+
+    for _ in 0..size {
+        let from = loop {
+            let stack = random.range(0..stacks);
+            if heights[stack] > 0 {
+                break stack;
+            }
+        };
+        let to = (from + random.range(1..stacks)) % stacks;
+        let quantity = random.range(1..heights[from] + 1);
+        heights[from] -= quantity;
+        heights[to] += quantity;
+        plan += &format!("move {} from {} to {}\n", quantity, from + 1, to + 1);
+    }
+    plan
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
-    use crate::day_5::{get_top_items, parse_initial_state, rearrange_crates, Stack};
+    use crate::day_5::{get_top_items, parse_initial_state, parse_moves, rearrange_crates, Crane, Stack};
+    use crate::error::Error;
+    use crate::trace::Trace;
 
     #[test]
     fn stack() {
@@ -145,9 +317,9 @@ mod tests {
         assert_eq!(stack.stack, [1, 2, 3]);
         stack.push_vector(&mut vec![4, 5, 6]);
         assert_eq!(stack.stack, [1, 2, 3, 4, 5, 6]);
-        assert_eq!(stack.pop(), 6);
+        assert_eq!(stack.pop(), Some(6));
         assert_eq!(stack.stack, [1, 2, 3, 4, 5]);
-        assert_eq!(stack.pop_many(2), [4, 5]);
+        assert_eq!(stack.pop_many(2), Some(vec![4, 5]));
         assert_eq!(stack.stack, [1, 2, 3]);
     }
 
@@ -205,7 +377,7 @@ mod tests {
     #[test]
     fn parse_example_initial_state() {
         let example = include_str!("resources/test/day_5_crates_example.txt");
-        let parsed = parse_initial_state(example);
+        let parsed = parse_initial_state(example).unwrap();
         let expected = get_initial_state();
         assert_eq!(parsed, expected);
     }
@@ -214,7 +386,7 @@ mod tests {
     fn rearrange_simple_pop() {
         let example = include_str!("resources/test/day_5_crates_example.txt");
         let mut to_rearrange = get_initial_state();
-        rearrange_crates(example, &mut to_rearrange, true);
+        rearrange_crates(&parse_moves(example).unwrap(), &mut to_rearrange, true).unwrap();
         let expected = get_rearranged_state_simple_pop();
         assert_eq!(to_rearrange, expected);
     }
@@ -223,7 +395,7 @@ mod tests {
     fn rearrange_multi_pop() {
         let example = include_str!("resources/test/day_5_crates_example.txt");
         let mut to_rearrange = get_initial_state();
-        rearrange_crates(example, &mut to_rearrange, false);
+        rearrange_crates(&parse_moves(example).unwrap(), &mut to_rearrange, false).unwrap();
         let expected = get_rearranged_state_multi_pop();
         assert_eq!(to_rearrange, expected);
     }
@@ -233,4 +405,27 @@ mod tests {
         let expected = get_top_items(get_rearranged_state_multi_pop());
         assert_eq!(expected.as_str(), "MCD");
     }
+
+    #[test]
+    fn malformed_movement() {
+        let example = include_str!("resources/test/day_5_crates_example.txt").replace("move 2 from 2 to 1", "move 2 from 2");
+        assert_eq!(parse_moves(&example), Err(Error::parse(5, 8, "move 2 from 2", "Expected a movement in the form of 'move 1 from 2 to 1'").at_column(14)));
+    }
+
+    #[test]
+    fn moving_from_empty_stack() {
+        let mut to_rearrange = get_initial_state();
+        let moves = parse_moves("1\n\nmove 2 from 3 to 1").unwrap();
+        assert!(rearrange_crates(&moves, &mut to_rearrange, true).is_err());
+    }
+
+    #[test]
+    fn trace_moves() {
+        let input = include_str!("resources/test/day_5_crates_example.txt");
+        let mut crane = Crane::new(get_initial_state(), parse_moves(input).unwrap(), true);
+        let trace = Trace::record(&mut crane, usize::MAX).unwrap();
+        assert_eq!(trace.states.len(), 5);
+        assert_eq!(trace.states[1], "move 1 | 1: [ZND] 2: [MC] 3: [P]");
+        assert_eq!(trace.states[4], "move 4 | 1: [C] 2: [M] 3: [PDNZ]");
+    }
 }
@@ -1,11 +1,51 @@
-#[allow(dead_code)]
-pub fn main() {
-    let communication = include_str!("resources/day_6_packet_transfer.txt");
-    println!("Prefix is {} long", find_communication_prefix(communication, 4));
-    println!("First communication after {} letters", find_communication_prefix(communication, 14))
+use crate::error::{Error, Result};
+use crate::random::Random;
+use crate::solution::Solution;
+
+/// Day 6: Tuning Trouble, start markers in the datastream
+pub struct Day6;
+
+impl Solution for Day6 {
+    type Input = String;
+    type Params = ();
+
+    fn name(&self) -> &'static str {
+        "Tuning Trouble"
+    }
+
+    fn day(&self) -> usize {
+        6
+    }
+
+    fn input(&self) -> &'static str {
+        "day_6_packet_transfer.txt"
+    }
+
+    fn parse(&self, input: &str) -> Result<String> {
+        Ok(input.trim().to_string())
+    }
+
+    fn part1(&self, communication: &String, _params: &()) -> Result<String> {
+        find_marker(communication, 4)
+    }
+
+    fn part2(&self, communication: &String, _params: &()) -> Result<String> {
+        find_marker(communication, 14)
+    }
+
+    fn generate(&self, random: &mut Random, size: usize) -> String {
+        generate(random, size)
+    }
+}
+
+fn find_marker(communication: &str, len: usize) -> Result<String> {
+    find_communication_prefix(communication, len)
+        .map(|prefix| prefix.to_string())
+        .ok_or_else(|| Error::solve(6, &format!("No {} distinct characters in a row were found", len)))
 }
 
-fn find_communication_prefix(communication: &str, len: usize) -> u32 {
+/// Number of characters processed before the first `len` distinct characters in a row
+pub fn find_communication_prefix(communication: &str, len: usize) -> Option<u32> {
     let mut tmp_marker = vec![];
     for (idx, character) in communication.chars().enumerate() {
         tmp_marker.push(character);
@@ -15,11 +55,11 @@ fn find_communication_prefix(communication: &str, len: usize) -> u32 {
         }
 
         if tmp_marker.len() == len && !contains_duplicate(&tmp_marker) {
-            return u32::try_from(idx).unwrap() + 1;
+            return Some(u32::try_from(idx).unwrap() + 1);
         }
     }
 
-    panic!()
+    None
 }
 
 fn contains_duplicate(list: &Vec<char>) -> bool {
@@ -30,7 +70,19 @@ fn contains_duplicate(list: &Vec<char>) -> bool {
         }
         tmp.push(*character);
     }
-    return false;
+    false
+}
+
+/// Random datastream of `size` characters (at least 14) with a start-of-message marker somewhere in it
+pub fn generate(random: &mut Random, size: usize) -> String {
+    let size = size.max(14);
+    let mut letters = ('a'..='z').collect::<Vec<char>>();
+    // few letters make accidental markers rare
+    let mut stream = (0..size).map(|_| *random.choose(&letters[..8])).collect::<Vec<char>>();
+    random.shuffle(&mut letters);
+    let start = random.range(0..size - 13);
+    stream[start..start + 14].copy_from_slice(&letters[..14]);
+    stream.into_iter().collect::<String>() + "\n"
 }
 
 #[cfg(test)]
@@ -41,7 +93,7 @@ mod tests {
     fn multiple_communications_find_prefix() {
         let multiple_communications = include_str!("resources/test/day_6_packet_transfer_multiline.txt").lines().collect::<Vec<&str>>();
         let correct_results: Vec<u32> = vec![7, 5, 6, 10, 11];
-        let results = multiple_communications.iter().map(|comm| find_communication_prefix(comm, 4)).collect::<Vec<u32>>();
+        let results = multiple_communications.iter().map(|comm| find_communication_prefix(comm, 4).unwrap()).collect::<Vec<u32>>();
         assert_eq!(results, correct_results);
     }
 
@@ -49,8 +101,12 @@ mod tests {
     fn multiple_communications_find_communication() {
         let multiple_communications = include_str!("resources/test/day_6_packet_transfer_multiline.txt").lines().collect::<Vec<&str>>();
         let correct_results: Vec<u32> = vec![19, 23, 23, 29, 26];
-        let results = multiple_communications.iter().map(|comm| find_communication_prefix(comm, 14)).collect::<Vec<u32>>();
+        let results = multiple_communications.iter().map(|comm| find_communication_prefix(comm, 14).unwrap()).collect::<Vec<u32>>();
         assert_eq!(results, correct_results);
     }
-}
 
+    #[test]
+    fn communication_without_marker() {
+        assert_eq!(find_communication_prefix("abcabcabc", 4), None);
+    }
+}
@@ -1,52 +1,138 @@
-#[allow(dead_code)]
-pub fn main() {
-    parse_input(include_str!("resources/day_7_file_system.txt"));
+use crate::error::{Error, Result};
+use crate::explain;
+use crate::params::{self, Params};
+use crate::random::Random;
+use crate::solution::Solution;
+
+/// Day 7: No Space Left On Device, directory sizes from terminal output
+pub struct Day7;
+
+impl Solution for Day7 {
+    type Input = FileSystem;
+    type Params = Day7Params;
+
+    fn name(&self) -> &'static str {
+        "No Space Left On Device"
+    }
+
+    fn day(&self) -> usize {
+        7
+    }
+
+    fn input(&self) -> &'static str {
+        "day_7_file_system.txt"
+    }
+
+    fn parse(&self, input: &str) -> Result<FileSystem> {
+        parse_input(input)
+    }
+
+    fn part1(&self, file_system: &FileSystem, params: &Day7Params) -> Result<String> {
+        Ok(file_system
+            .get_all_directories()
+            .iter()
+            .filter(|(_, size)| *size <= params.small_size)
+            .fold(0, |acc, (_, size)| acc + size)
+            .to_string())
+    }
+
+    fn part2(&self, file_system: &FileSystem, params: &Day7Params) -> Result<String> {
+        let mut dirs = file_system.get_all_directories();
+
+        let (_, root_size) = *dirs.first().unwrap();
+        let free_space = params
+            .disk_size
+            .checked_sub(root_size)
+            .ok_or_else(|| Error::solve(7, "Files don't fit on the disk"))?;
+        let to_be_deleted = params.needed_space.saturating_sub(free_space);
+
+        dirs.sort_by_key(|(_, size)| *size);
+        let (name, dir_to_be_deleted) = dirs
+            .iter()
+            .find(|(_, size)| *size > to_be_deleted)
+            .ok_or_else(|| Error::solve(7, "No directory is big enough to free the needed space"))?;
+        if explain::enabled() {
+            let candidates = dirs.iter().filter(|(_, size)| *size > to_be_deleted).count();
+            explain::note("used space", root_size);
+            explain::note("free space", free_space);
+            explain::note("space to free", to_be_deleted);
+            explain::note("deleted directory", format!("{} ({})", name, dir_to_be_deleted));
+            explain::note("why", format!("smallest of the {} directories big enough to free the space", candidates));
+        }
+        Ok(dir_to_be_deleted.to_string())
+    }
+
+    fn generate(&self, random: &mut Random, size: usize) -> String {
+        generate(random, size)
+    }
+}
+
+/// Sizes from the puzzle text
+pub struct Day7Params {
+    /// Directories of at most this size are summed in the first part
+    pub small_size: usize,
+    pub disk_size: usize,
+    /// Free space the update needs
+    pub needed_space: usize,
+}
+
+impl Default for Day7Params {
+    fn default() -> Day7Params {
+        Day7Params { small_size: 100_000, disk_size: 70_000_000, needed_space: 30_000_000 }
+    }
+}
+
+impl Params for Day7Params {
+    fn set(&mut self, key: &str, value: &str) -> std::result::Result<(), String> {
+        match key {
+            "small_size" => self.small_size = params::value(key, value)?,
+            "disk_size" => self.disk_size = params::value(key, value)?,
+            "needed_space" => self.needed_space = params::value(key, value)?,
+            _ => return Err(format!("unknown parameter '{}', use small_size, disk_size or needed_space", key)),
+        }
+        Ok(())
+    }
 }
 
-fn parse_input(input: &str) {
-    let file_system = &mut FileSystem::new();
-    for line in input.lines() {
+/// Rebuild the file system from the `cd` and `ls` terminal output
+pub fn parse_input(input: &str) -> Result<FileSystem> {
+    let mut file_system = FileSystem::new();
+    for (index, line) in input.lines().enumerate() {
+        let error = |message: &str| Error::parse(7, index + 1, line, message);
+        if file_system.files.is_empty() && line != "$ cd /" {
+            return Err(error("Terminal output has to start with '$ cd /'"));
+        }
+
         if line.starts_with("$ cd") {
             if line.ends_with("..") {
                 file_system.parent_one_up();
             } else if line.contains("cd /") {
-                file_system.add_file("/", 0, true);
-            } else {
-                file_system.set_child_as_parent(line.split_whitespace().last().unwrap());
+                file_system.set_root();
+            } else if !file_system.set_child_as_parent(line.split_whitespace().last().unwrap()) {
+                return Err(error("Unknown directory"));
             }
         } else if line.starts_with("dir") {
+            let name = line.split_whitespace().nth(1).ok_or_else(|| error("Directory is missing a name"))?;
+            file_system.add_file(name, 0, true);
+        } else if !line.starts_with('$') {
             let parts = line.split_whitespace().collect::<Vec<&str>>();
-            file_system.add_file(parts.get(1).unwrap(), 0, true);
-        } else if !line.starts_with("$") {
-            let parts = line.split_whitespace().collect::<Vec<&str>>();
-            file_system.add_file(parts.get(1).unwrap(), parts.get(0).unwrap().parse::<usize>().unwrap(), false);
+            let (size, name) = match parts[..] {
+                [size, name] => (size.parse::<usize>().map_err(|_| error("Couldn't parse file size"))?, name),
+                _ => return Err(error("Expected a file in the form of '1234 name'")),
+            };
+            file_system.add_file(name, size, false);
+        } else if line != "$ ls" {
+            return Err(error("Unknown command"));
         }
     }
-
-    let dirs = file_system.get_all_directories();
-
-    let (_, root_size) = dirs.first().unwrap();
-    let total_space: usize = 70_000_000;
-    let needed_free_space: usize = 30_000_000;
-    let to_be_deleted = needed_free_space - (total_space - root_size);
-    println!("Root size: {}", root_size);
-    println!("Total size: {}", total_space);
-    println!("Needed space: {}", needed_free_space);
-    println!("{} needs to be deleted", to_be_deleted);
-
-    let sum = dirs
-        .iter()
-        .filter(|(_, size)| *size <= 100000)
-        .fold(0, |acc, (_, size)| acc + size);
-    println!("Sum of directories with at max 100000 in size: {}", sum);
-
-    let mut dirs_sorted = dirs.clone();
-    dirs_sorted.sort_by(|(_, dir_1), (_, dir_2)| dir_1.cmp(dir_2));
-    let (_, dir_to_be_deleted) = dirs_sorted.iter().find(|(_, size)| *size > to_be_deleted).unwrap();
-    println!("Dir with size {} to be deleted", dir_to_be_deleted);
+    if file_system.files.is_empty() {
+        return Err(Error::parse(7, 1, "", "Terminal output is empty"));
+    }
+    Ok(file_system)
 }
 
-struct FileSystem {
+/// Tree of files and directories, the root directory is always first
+pub struct FileSystem {
     files: Vec<File>,
     current_parent: usize,
 }
@@ -77,20 +163,34 @@ impl FileSystem {
         }
     }
 
+    /// Switch to the root directory, creating it on the first visit
+    fn set_root(&mut self) {
+        if self.files.is_empty() {
+            self.add_file("/", 0, true);
+        }
+        self.current_parent = 0;
+    }
+
     fn parent_one_up(&mut self) {
         self.current_parent = self.files.get(self.current_parent).unwrap().parent
     }
 
-    fn set_child_as_parent(&mut self, name: &str) {
-        self.current_parent = self
+    /// Returns false when there is no such directory in the current one
+    fn set_child_as_parent(&mut self, name: &str) -> bool {
+        let child = self
             .files
             .get(self.current_parent)
             .unwrap()
             .children
             .iter()
-            .find(|child| self.files.get(**child).unwrap().name == name)
-            .unwrap()
-            .clone();
+            .find(|child| self.files.get(**child).unwrap().name == name && self.files.get(**child).unwrap().is_directory);
+        match child {
+            Some(child) => {
+                self.current_parent = *child;
+                true
+            }
+            None => false,
+        }
     }
 
     fn calculate_dir_size(&self, idx: usize) -> usize {
@@ -105,7 +205,8 @@ impl FileSystem {
         size
     }
 
-    fn get_all_directories(&self) -> Vec<(&str, usize)> {
+    /// Name and total size (including subdirectories) of every directory
+    pub fn get_all_directories(&self) -> Vec<(&str, usize)> {
         let mut result: Vec<(&str, usize)> = Vec::new();
         for file in 0..self.files.len() {
             if self.files.get(file).unwrap().is_directory {
@@ -125,4 +226,89 @@ struct File {
     is_directory: bool,
 }
 
+/// Random terminal output exploring a file system with `size` files in about `size / 4` directories
+///
+/// Files are small enough for the whole file system to take at most 40000000 of the disk.
+pub fn generate(random: &mut Random, size: usize) -> String {
+    let size = size.max(1);
+    let directories = size / 4 + 1;
+    // parent of every directory but the root, parents always come before their children
+    let parents = (1..directories).map(|directory| random.range(0..directory)).collect::<Vec<usize>>();
+    let max_size = (40_000_000 / size).clamp(1, 300_000);
+    let mut files = vec![vec![]; directories];
+    for file in 0..size {
+        let name = format!("f{}.{}", file, random.choose(&["txt", "dat", "log"]));
+        files[random.range(0..directories)].push((name, random.range(1..max_size + 1)));
+    }
+
+    let mut output = String::from("$ cd /\n");
+    list_directory(0, &parents, &files, &mut output);
+    output
+}
+
+fn list_directory(directory: usize, parents: &[usize], files: &[Vec<(String, usize)>], output: &mut String) {
+    let children = (1..=parents.len()).filter(|child| parents[child - 1] == directory).collect::<Vec<usize>>();
+    output.push_str("$ ls\n");
+    for child in &children {
+        output.push_str(&format!("dir d{}\n", child));
+    }
+    for (name, size) in &files[directory] {
+        output.push_str(&format!("{} {}\n", size, name));
+    }
+    for child in children {
+        output.push_str(&format!("$ cd d{}\n", child));
+        list_directory(child, parents, files, output);
+        output.push_str("$ cd ..\n");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day_7::{Day7, Day7Params};
+    use crate::error::Error;
+    use crate::explain::{self, Note};
+    use crate::solution::Solution;
+
+    #[test]
+    fn sum_of_small_directories() {
+        let file_system = Day7.parse(include_str!("resources/test/day_7_file_system_example.txt")).unwrap();
+        assert_eq!(Day7.part1(&file_system, &Day7Params::default()).unwrap(), "95437");
+    }
+
+    #[test]
+    fn directory_to_be_deleted() {
+        let file_system = Day7.parse(include_str!("resources/test/day_7_file_system_example.txt")).unwrap();
+        assert_eq!(Day7.part2(&file_system, &Day7Params::default()).unwrap(), "24933642");
+    }
+
+    #[test]
+    fn smaller_disk() {
+        let file_system = Day7.parse(include_str!("resources/test/day_7_file_system_example.txt")).unwrap();
+        let params = Day7Params { small_size: 1000, disk_size: 50_000_000, ..Day7Params::default() };
+        assert_eq!(Day7.part1(&file_system, &params).unwrap(), "584");
+        assert_eq!(Day7.part2(&file_system, &params).unwrap(), "48381165");
+    }
+
+    #[test]
+    fn unknown_directory() {
+        let result = Day7.parse("$ cd /\n$ ls\ndir a\n$ cd b");
+        assert!(matches!(result, Err(Error::Parse { line: 4, .. })));
+    }
 
+    #[test]
+    fn explain_deleted_directory() {
+        let file_system = Day7.parse(include_str!("resources/test/day_7_file_system_example.txt")).unwrap();
+        let (answer, notes) = explain::record(|| Day7.part2(&file_system, &Day7Params::default()));
+        assert_eq!(answer.unwrap(), "24933642");
+        assert_eq!(
+            notes.iter().map(Note::to_string).collect::<Vec<String>>(),
+            [
+                "used space: 48381165",
+                "free space: 21618835",
+                "space to free: 8381165",
+                "deleted directory: d (24933642)",
+                "why: smallest of the 2 directories big enough to free the space",
+            ]
+        );
+    }
+}
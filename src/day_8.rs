@@ -1,176 +1,195 @@
-#[allow(dead_code)]
-pub fn main() {
-    let forest = Forest::new(include_str!("resources/day_8_forest.txt"));
-
-    forest.print();
-    println!("Number of visible trees: {}", forest.count_all_visible());
-    println!("The best scenic view has score of {}", forest.get_best_scenic_score());
-}
+use crate::error::Result;
+use crate::explain;
+use crate::geometry::{Direction, Point};
+use crate::grid::Grid;
+use crate::image::Picture;
+use crate::log;
+use crate::random::Random;
+use crate::solution::Solution;
 
+/// Day 8: Treetop Tree House, visibility and scenic scores of trees
+pub struct Day8;
 
-struct Forest {
-    width: u16,
-    trees: Vec<u8>,
-}
+impl Solution for Day8 {
+    type Input = Forest;
+    type Params = ();
 
-impl Forest {
-    fn new(input: &str) -> Forest {
-        Forest {
-            width: input.lines().last().unwrap().len() as u16,
-            trees: input.lines().map(|line| line.chars().map(|letter| letter.to_digit(10).unwrap() as u8)).flatten().collect(),
-        }
+    fn name(&self) -> &'static str {
+        "Treetop Tree House"
     }
 
-    fn right(&self, x: u16, y: u16) -> DirectionalIterator {
-        self.create_directional_iterator(x, y, |x| x + 1, |y| y)
+    fn day(&self) -> usize {
+        8
     }
 
-    fn left(&self, x: u16, y: u16) -> DirectionalIterator {
-        self.create_directional_iterator(x, y, |x| if x == 0 { u16::MAX } else { x - 1 }, |y| y)
+    fn input(&self) -> &'static str {
+        "day_8_forest.txt"
     }
 
-    fn up(&self, x: u16, y: u16) -> DirectionalIterator {
-        self.create_directional_iterator(x, y, |x| x, |y| if y == 0 { u16::MAX } else { y - 1 })
+    fn parse(&self, input: &str) -> Result<Forest> {
+        Forest::new(input)
     }
 
-    fn down(&self, x: u16, y: u16) -> DirectionalIterator {
-        self.create_directional_iterator(x, y, |x| x, |y| y + 1)
+    fn part1(&self, forest: &Forest, _params: &()) -> Result<String> {
+        log::debug(format_args!("forest\n{}", forest.trees));
+        Ok(forest.count_all_visible().to_string())
     }
 
-    fn create_directional_iterator<F: Fn(u16) -> u16, G: Fn(u16) -> u16>(&self, x: u16, y: u16, x_closure: F, y_closure: G) -> DirectionalIterator {
-        let mut vec: Vec<u8> = Vec::new();
-
-        let mut index_x = x_closure(x);
-        let mut index_y = y_closure(y);
-        loop {
-            let tree = self.get_tree(index_x, index_y);
-            if tree.is_none() {
-                break;
+    fn part2(&self, forest: &Forest, _params: &()) -> Result<String> {
+        let Some((position, score)) = forest.get_best_scenic_tree() else {
+            return Ok("0".to_string());
+        };
+        if explain::enabled() {
+            explain::note("best tree", format!("{} of height {}", position, forest.get_tree(position).unwrap()));
+            for (direction, distance) in Direction::ORTHOGONAL.iter().zip(forest.get_viewing_distances(position)) {
+                explain::note(&format!("viewing distance {:?}", direction).to_lowercase(), distance);
             }
-            vec.push(tree.unwrap());
-            index_x = x_closure(index_x);
-            index_y = y_closure(index_y);
         }
-
-        DirectionalIterator { list: vec }
+        Ok(score.to_string())
     }
 
-    fn get_height(&self) -> u16 {
-        self.trees.len() as u16 / self.width
+    fn generate(&self, random: &mut Random, size: usize) -> String {
+        generate(random, size)
     }
 
-    fn two_dim_to_one_dim(&self, x: u16, y: u16) -> Option<usize> {
-        if x >= self.width || y >= self.get_height() {
-            return None;
-        }
-        return Some((self.width * y + x) as usize);
+    fn export(&self, forest: &Forest, _params: &()) -> Option<Picture> {
+        Some(forest.scenic_heatmap())
     }
+}
 
-    fn get_tree(&self, x: u16, y: u16) -> Option<u8> {
-        let a = self.two_dim_to_one_dim(x, y);
-        self.trees.get(a.or(Some(usize::MAX)).unwrap()).cloned()
-    }
+/// Grid of tree heights, `x` is the column and `y` the row from the top left corner
+pub struct Forest {
+    trees: Grid<u8>,
+}
 
-    fn print(&self) {
-        for (i, x) in self.trees.iter().enumerate() {
-            if i % self.width as usize == 0 {
-                print!("\n");
-            }
-            print!("{}", x);
-        }
-        print!("\n");
+impl Forest {
+    /// Parse rows of digits, all rows have to be equally long
+    pub fn new(input: &str) -> Result<Forest> {
+        let trees = Grid::parse(8, input, "forest", |letter| {
+            letter.to_digit(10).map(|height| height as u8).ok_or("Tree heights have to be digits")
+        })?;
+        Ok(Forest { trees })
     }
 
-    fn count_all_visible(&self) -> usize {
-        let mut counter = 0;
-
-        for x in 0..self.width {
-            for y in 0..self.get_height() {
-                if self.is_tree_visible(x, y) {
-                    counter += 1;
-                }
-            }
-        }
-
-        counter
+    pub fn get_width(&self) -> usize {
+        self.trees.width()
     }
 
-    fn is_tree_visible(&self, x: u16, y: u16) -> bool {
-        if self.is_tree_on_edge(x, y) {
-            return true;
-        }
+    /// Number of rows, not to be confused with heights of trees
+    pub fn get_height(&self) -> usize {
+        self.trees.height()
+    }
 
-        let tree_height = self.get_tree(x, y).unwrap();
-        let predicate = |tree: &u8| *tree >= tree_height;
+    /// Height of the tree, `None` outside of the forest
+    pub fn get_tree(&self, position: Point) -> Option<u8> {
+        self.trees.get(position).copied()
+    }
 
-        self.right(x, y).find(&predicate).is_none() ||
-            self.left(x, y).find(&predicate).is_none() ||
-            self.up(x, y).find(&predicate).is_none() ||
-            self.down(x, y).find(&predicate).is_none()
+    /// Number of trees visible from outside of the forest
+    pub fn count_all_visible(&self) -> usize {
+        self.trees.positions().filter(|position| self.is_tree_visible(*position)).count()
     }
 
-    fn is_tree_on_edge(&self, x: u16, y: u16) -> bool {
-        x == 0 || x == (self.width - 1) || y == 0 || y == (self.get_height() - 1)
+    /// A tree is visible when all trees between it and an edge are shorter, trees on the edge
+    /// are always visible
+    pub fn is_tree_visible(&self, position: Point) -> bool {
+        let tree_height = self.trees.get(position).unwrap();
+        Direction::ORTHOGONAL
+            .iter()
+            .any(|direction| self.trees.ray(position, *direction).all(|tree| tree < tree_height))
     }
 
-    fn get_scenic_score(&self, x: u16, y: u16) -> usize {
-        let tree_height = self.get_tree(x, y).unwrap();
-        let predicate = |(_, tree): &(usize, u8)| *tree >= tree_height;
-        let add_one = |(index, tree)| (index + 1, tree);
-        let (left, _) = self.left(x, y).enumerate().map(&add_one).find(&predicate).or(Some((x as usize, 0))).unwrap();
-        let (right, _) = self.right(x, y).enumerate().map(&add_one).find(&predicate).or(Some(((self.width - 1 - x) as usize, 0))).unwrap();
-        let (up, _) = self.up(x, y).enumerate().map(&add_one).find(&predicate).or(Some((y as usize, 0))).unwrap();
-        let (down, _) = self.down(x, y).enumerate().map(&add_one).find(&predicate).or(Some(((self.get_height() - 1 - y) as usize, 0))).unwrap();
-        left * right * up * down
+    /// Product of viewing distances in all four directions
+    pub fn get_scenic_score(&self, position: Point) -> usize {
+        self.get_viewing_distances(position).iter().product()
     }
 
-    fn get_best_scenic_score(&self) -> usize {
-        let mut score = 0;
-        for x in 0..self.width {
-            for y in 0..self.get_height() {
-                let s = self.get_scenic_score(x, y);
-                if s > score {
-                    score = s;
+    /// Number of trees seen from the tree in each [orthogonal](Direction::ORTHOGONAL) direction,
+    /// up to the edge or the first tree at least as tall
+    pub fn get_viewing_distances(&self, position: Point) -> [usize; 4] {
+        let tree_height = self.trees.get(position).unwrap();
+        Direction::ORTHOGONAL.map(|direction| {
+            let mut distance = 0;
+            for tree in self.trees.ray(position, direction) {
+                distance += 1;
+                if tree >= tree_height {
+                    break;
                 }
             }
-        }
-        score
+            distance
+        })
     }
-}
 
-struct DirectionalIterator {
-    list: Vec<u8>,
-}
+    pub fn get_best_scenic_score(&self) -> usize {
+        self.get_best_scenic_tree().map_or(0, |(_, score)| score)
+    }
 
-impl Iterator for DirectionalIterator {
-    type Item = u8;
+    /// Position and scenic score of the tree with the best one, the first in reading order on ties
+    pub fn get_best_scenic_tree(&self) -> Option<(Point, usize)> {
+        self.trees
+            .positions()
+            .map(|position| (position, self.get_scenic_score(position)))
+            .reduce(|best, tree| if tree.1 > best.1 { tree } else { best })
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.list.len() > 0 {
-            let first_item = self.list.first().unwrap().clone();
-            self.list.remove(0);
-            Some(first_item)
-        } else {
-            None
+    /// Scenic scores relative to the best one, square rooted so the few best trees don't outshine the rest
+    pub fn scenic_heatmap(&self) -> Picture {
+        let best = (self.get_best_scenic_score() as f64).sqrt().max(1.0);
+        let mut picture = Grid::new(self.trees.width(), self.trees.height(), None);
+        for position in self.trees.positions() {
+            picture[position] = Some((self.get_scenic_score(position) as f64).sqrt() / best);
         }
+        picture
     }
 }
 
+/// Random square forest with `size` rows of tree heights
+pub fn generate(random: &mut Random, size: usize) -> String {
+    let size = size.max(1);
+    (0..size)
+        .map(|_| (0..size).map(|_| char::from_digit(random.range(0..10) as u32, 10).unwrap()).chain(['\n']).collect::<String>())
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::day_8::Forest;
+    use crate::day_8::{Day8, Forest};
+    use crate::error::Error;
+    use crate::explain::{self, Note};
+    use crate::geometry::Point;
+    use crate::solution::Solution;
 
     #[test]
     fn count_visible_trees() {
         let input = include_str!("resources/test/day_8_forest_example.txt");
-        let forest = Forest::new(input);
+        let forest = Forest::new(input).unwrap();
         assert_eq!(forest.count_all_visible(), 21);
     }
 
     #[test]
     fn get_best_scenic_score() {
         let input = include_str!("resources/test/day_8_forest_example.txt");
-        let forest = Forest::new(input);
+        let forest = Forest::new(input).unwrap();
         assert_eq!(forest.get_best_scenic_score(), 8);
+
+        let heatmap = forest.scenic_heatmap();
+        assert_eq!(heatmap[Point::new(2, 3)], Some(1.0));
+        assert_eq!(heatmap[Point::new(0, 0)], Some(0.0));
+    }
+
+    #[test]
+    fn ragged_forest() {
+        assert_eq!(Forest::new("303\n25\n").err(), Some(Error::parse(8, 2, "25", "All rows of the forest have to be equally long")));
+    }
+
+    #[test]
+    fn explain_best_tree() {
+        let forest = Forest::new(include_str!("resources/test/day_8_forest_example.txt")).unwrap();
+        assert_eq!(forest.get_viewing_distances(Point::new(2, 3)), [2, 2, 2, 1]);
+        let (answer, notes) = explain::record(|| Day8.part2(&forest, &()));
+        assert_eq!(answer.unwrap(), "8");
+        let notes = notes.iter().map(Note::to_string).collect::<Vec<String>>();
+        assert_eq!(notes[0], "best tree: 2,3 of height 5");
+        assert_eq!(notes[1..], ["viewing distance right: 2", "viewing distance left: 2", "viewing distance up: 2", "viewing distance down: 1"]);
     }
 }
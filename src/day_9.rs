@@ -1,150 +1,263 @@
-use std::str::FromStr;
-
-#[allow(dead_code)]
-pub fn main() {
-    let input = include_str!("resources/day_9_ropes.txt");
-    let mut grid = Grid::new(2);
-    for (direction, cycles) in input.lines().map(|line| Direction::parse(line)) {
-        for _ in 0..cycles {
-            grid.move_head(&direction);
-        }
+use crate::error::{Error, Result};
+use crate::geometry::{Direction, Point};
+use crate::grid::SparseGrid;
+use crate::image::{self, Picture};
+use crate::params::{self, Params};
+use crate::random::Random;
+use crate::simulation::{Drawable, Driver, Simulation, Traceable};
+use crate::solution::Solution;
+use crate::trace::Trace;
+use crate::visualize::{Frame, Visualizer};
+
+/// Day 9: Rope Bridge, positions visited by the tail of a rope
+pub struct Day9;
+
+impl Solution for Day9 {
+    type Input = Vec<(Direction, usize)>;
+    type Params = Day9Params;
+
+    fn name(&self) -> &'static str {
+        "Rope Bridge"
+    }
+
+    fn day(&self) -> usize {
+        9
+    }
+
+    fn input(&self) -> &'static str {
+        "day_9_ropes.txt"
     }
-    println!("Tail has visited {} positions", grid.count_visited());
 
+    fn parse(&self, input: &str) -> Result<Vec<(Direction, usize)>> {
+        parse_moves(input)
+    }
+
+    fn part1(&self, moves: &Vec<(Direction, usize)>, params: &Day9Params) -> Result<String> {
+        Ok(count_tail_visits(moves, params.short_rope)?.to_string())
+    }
+
+    fn part2(&self, moves: &Vec<(Direction, usize)>, params: &Day9Params) -> Result<String> {
+        Ok(count_tail_visits(moves, params.long_rope)?.to_string())
+    }
+
+    fn generate(&self, random: &mut Random, size: usize) -> String {
+        generate(random, size)
+    }
 
-    grid = Grid::new(10);
-    for (direction, cycles) in input.lines().map(|line| Direction::parse(line)) {
-        for _ in 0..cycles {
-            grid.move_head(&direction);
+    /// Positions visited by the tail of the short rope, brighter where the long one's tail went too
+    fn export(&self, moves: &Vec<(Direction, usize)>, params: &Day9Params) -> Option<Picture> {
+        let (mut short, mut long) = (Rope::new(params.short_rope), Rope::new(params.long_rope));
+        for (direction, cycles) in moves {
+            for _ in 0..*cycles {
+                short.move_head(direction);
+                long.move_head(direction);
+            }
+        }
+        let mut visited = SparseGrid::new();
+        for (position, _) in short.visited.iter() {
+            visited.insert(position, if long.visited.contains(position) { 1.0 } else { 0.5 });
         }
+        Some(image::from_sparse(&visited, |value| *value))
+    }
+
+    fn visualize(&self, moves: &Vec<(Direction, usize)>, params: &Day9Params) -> Option<Box<dyn Visualizer>> {
+        Some(Box::new(Driver::new(RopeSimulation::new(moves, params.long_rope))))
     }
-    println!("Tail has visited {} positions", grid.count_visited());
+
+    /// Knots of the long rope after every step of its head
+    fn trace(&self, moves: &Vec<(Direction, usize)>, params: &Day9Params) -> Result<Option<Trace>> {
+        Trace::record(&mut RopeSimulation::new(moves, params.long_rope), usize::MAX).map(Some)
+    }
+}
+
+/// Number of knots of the ropes in both parts
+pub struct Day9Params {
+    pub short_rope: usize,
+    pub long_rope: usize,
 }
 
-enum Direction {
-    Right,
-    Left,
-    Up,
-    Down,
+impl Default for Day9Params {
+    fn default() -> Day9Params {
+        Day9Params { short_rope: 2, long_rope: 10 }
+    }
 }
 
-impl Direction {
-    fn parse(line: &str) -> (Direction, usize) {
-        let parts = line.split_whitespace().collect::<Vec<&str>>();
-        match parts[0].to_lowercase() {
-            x if x.contains("r") => (Direction::Right, usize::from_str(parts[1]).unwrap()),
-            x if x.contains("l") => (Direction::Left, usize::from_str(parts[1]).unwrap()),
-            x if x.contains("u") => (Direction::Up, usize::from_str(parts[1]).unwrap()),
-            x if x.contains("d") => (Direction::Down, usize::from_str(parts[1]).unwrap()),
-            _ => panic!()
+impl Params for Day9Params {
+    fn set(&mut self, key: &str, value: &str) -> std::result::Result<(), String> {
+        let rope = match key {
+            "short_rope" => &mut self.short_rope,
+            "long_rope" => &mut self.long_rope,
+            _ => return Err(format!("unknown parameter '{}', use short_rope or long_rope", key)),
+        };
+        let knots = params::value(key, value)?;
+        if knots == 0 {
+            return Err(format!("parameter '{}' has to be at least one knot", key));
         }
+        *rope = knots;
+        Ok(())
     }
 }
 
-struct Grid {
-    positions: Vec<Position>,
-    rope: Vec<usize>,
+/// Direction and number of steps of every head move
+pub fn parse_moves(input: &str) -> Result<Vec<(Direction, usize)>> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(index, line)| Direction::parse_move(line).ok_or_else(|| Error::parse(9, index + 1, line, "Expected a move in the form of 'R 4'")))
+        .collect()
 }
 
-impl Grid {
-    fn new(knots: usize) -> Grid {
-        Grid {
-            positions: vec![Position { x: 0, y: 0, visited: true }],
-            rope: vec![0; knots],
-        }
+/// Number of positions the tail of a rope with the given number of knots visits at least once
+pub fn count_tail_visits(moves: &[(Direction, usize)], knots: usize) -> Result<usize> {
+    let mut simulation = RopeSimulation::new(moves, knots);
+    simulation.run_until(|_| false)?;
+    Ok(simulation.rope.count_visited())
+}
+
+/// Rope moving on an unbounded grid, remembering positions visited by its tail
+#[derive(Clone)]
+pub struct Rope {
+    knots: Vec<Point>,
+    visited: SparseGrid<char>,
+}
+
+impl Rope {
+    pub fn new(knots: usize) -> Rope {
+        let mut visited = SparseGrid::new();
+        visited.insert(Point::ORIGIN, '#');
+        Rope { knots: vec![Point::ORIGIN; knots], visited }
     }
 
-    fn move_head(&mut self, direction: &Direction) {
-        let current_head = self.get_current_head_position();
-        let index = match direction {
-            Direction::Right => { self.get_position_index(current_head.x + 1, current_head.y) }
-            Direction::Left => { self.get_position_index(current_head.x - 1, current_head.y) }
-            Direction::Up => { self.get_position_index(current_head.x, current_head.y - 1) }
-            Direction::Down => { self.get_position_index(current_head.x, current_head.y + 1) }
-        };
-        *self.rope.first_mut().unwrap() = index;
-        for x in 0..(self.rope.len() - 1) {
-            self.recalculate_knots(x, x + 1);
+    /// Move the head by one step and let the rest of the knots follow
+    pub fn move_head(&mut self, direction: &Direction) {
+        self.knots[0] = self.knots[0].step(*direction);
+        for knot in 1..self.knots.len() {
+            self.follow(knot);
         }
+        self.visited.insert(*self.knots.last().unwrap(), '#');
     }
 
-    fn recalculate_knots(&mut self, knot_index_1: usize, knot_index_2: usize) {
-        let (head_x, head_y) = self.positions
-            .get(*self.rope.get(knot_index_1).unwrap())
-            .map(|pos| (pos.x, pos.y))
-            .unwrap();
-        let (tail_x, tail_y) = self.positions
-            .get(*self.rope.get(knot_index_2).unwrap())
-            .map(|pos| (pos.x, pos.y))
-            .unwrap();
-        let diff_x = head_x - tail_x;
-        let diff_y = head_y - tail_y;
-        if diff_x > 1 || diff_y > 1 || diff_x < -1 || diff_y < -1 {
-            if diff_x.abs() >= 1 && diff_y.abs() >= 1 {
-                *self.rope.get_mut(knot_index_2).unwrap() = self.get_position_index(tail_x + (diff_x / diff_x.abs()), tail_y + (diff_y / diff_y.abs()));
-            } else {
-                *self.rope.get_mut(knot_index_2).unwrap() = self.get_position_index(
-                    if diff_x == 0 { tail_x } else { tail_x + (diff_x / diff_x.abs()) },
-                    if diff_y == 0 { tail_y } else { tail_y + (diff_y / diff_y.abs()) },
-                );
-            }
-            if knot_index_2 == (self.rope.len() - 1) {
-                self.positions.get_mut(*self.rope.get(knot_index_2).unwrap()).unwrap().visited = true;
-            }
+    /// Move the knot towards the previous one when they stopped touching
+    fn follow(&mut self, knot: usize) {
+        let (head, tail) = (self.knots[knot - 1], self.knots[knot]);
+        if head.chebyshev(tail) > 1 {
+            self.knots[knot] += (head - tail).signum();
         }
     }
 
-    fn get_current_head_position(&self) -> &Position {
-        self.positions.get(*self.rope.first().unwrap()).unwrap()
+    pub fn count_visited(&self) -> usize {
+        self.visited.len()
+    }
+}
+
+/// Rope moving one step of the head at a time
+pub struct RopeSimulation {
+    rope: Rope,
+    steps: Vec<Direction>,
+    done: usize,
+}
+
+impl RopeSimulation {
+    pub fn new(moves: &[(Direction, usize)], knots: usize) -> RopeSimulation {
+        let steps = moves.iter().flat_map(|(direction, cycles)| vec![*direction; *cycles]).collect();
+        RopeSimulation { rope: Rope::new(knots), steps, done: 0 }
+    }
+}
+
+impl Simulation for RopeSimulation {
+    type State = (Rope, usize);
+
+    fn step(&mut self) -> Result<bool> {
+        let Some(direction) = self.steps.get(self.done) else {
+            return Ok(false);
+        };
+        self.rope.move_head(direction);
+        self.done += 1;
+        Ok(true)
+    }
+
+    fn snapshot(&self) -> (Rope, usize) {
+        (self.rope.clone(), self.done)
     }
 
-    fn get_position_index(&mut self, x: i16, y: i16) -> usize {
-        let mut index = self.positions.iter().position(|pos| pos.x == x && pos.y == y);
-        if index.is_none() {
-            self.positions.push(Position { x, y, visited: false });
-            index = Some(self.positions.len() - 1);
+    fn restore(&mut self, (rope, done): (Rope, usize)) {
+        self.rope = rope;
+        self.done = done;
+    }
+}
+
+impl Drawable for RopeSimulation {
+    /// Visited positions as `#`, knots numbered from the head `H`, the start as `s`
+    fn frame(&self) -> Frame {
+        let mut cells = self.rope.visited.clone();
+        cells.insert(Point::ORIGIN, 's');
+        for (index, knot) in self.rope.knots.iter().enumerate().rev() {
+            cells.insert(*knot, if index == 0 { 'H' } else { char::from_digit(index as u32 % 10, 10).unwrap() });
+        }
+        Frame {
+            cells,
+            highlighted: self.rope.knots.iter().copied().collect(),
+            focus: self.rope.knots[0],
+            caption: format!("head step {} of {}, tail visited {} positions", self.done, self.steps.len(), self.rope.count_visited()),
         }
-        index.unwrap()
     }
+}
 
-    fn count_visited(&self) -> usize {
-        self.positions.iter().filter(|pos| pos.visited).count()
+impl Traceable for RopeSimulation {
+    fn trace(&self) -> String {
+        let knots = self.rope.knots.iter().map(Point::to_string).collect::<Vec<String>>();
+        format!("knots {} | visited {}", knots.join(" "), self.rope.count_visited())
     }
 }
 
-#[derive(Copy, Clone)]
-struct Position {
-    x: i16,
-    y: i16,
-    visited: bool,
+/// Random `size` moves of the head
+pub fn generate(random: &mut Random, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| format!("{} {}\n", random.choose(&['R', 'L', 'U', 'D']), random.range(1..20)))
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::day_9::{Direction, Grid};
+    use crate::day_9::{parse_moves, Day9Params, Rope};
+    use crate::error::Error;
+    use crate::params::Params;
 
     #[test]
     fn count_visited_positions_two_knots() {
         let input = include_str!("resources/test/day_9_ropes_example.txt");
-        let mut grid = Grid::new(2);
-        for (direction, cycles) in input.lines().map(|line| Direction::parse(line)) {
+        let mut rope = Rope::new(2);
+        for (direction, cycles) in parse_moves(input).unwrap() {
             for _ in 0..cycles {
-                grid.move_head(&direction);
+                rope.move_head(&direction);
             }
         }
-        assert_eq!(grid.count_visited(), 13);
+        assert_eq!(rope.count_visited(), 13);
     }
 
     #[test]
     fn count_visited_positions_ten_knots() {
         let input = include_str!("resources/test/day_9_ropes_example.txt");
-        let mut grid = Grid::new(10);
-        for (direction, cycles) in input.lines().map(|line| Direction::parse(line)) {
+        let mut rope = Rope::new(10);
+        for (direction, cycles) in parse_moves(input).unwrap() {
             for _ in 0..cycles {
-                grid.move_head(&direction);
+                rope.move_head(&direction);
             }
         }
-        assert_eq!(grid.count_visited(), 1);
+        assert_eq!(rope.count_visited(), 1);
+    }
+
+    #[test]
+    fn unknown_direction() {
+        assert_eq!(parse_moves("R 4\nX 4").err(), Some(Error::parse(9, 2, "X 4", "Expected a move in the form of 'R 4'")));
     }
-}
 
+    #[test]
+    fn rope_params() {
+        let mut params = Day9Params::default();
+        assert_eq!(params.set("knots", "x"), Err("unknown parameter 'knots', use short_rope or long_rope".to_string()));
+        assert_eq!(params.set("long_rope", "0"), Err("parameter 'long_rope' has to be at least one knot".to_string()));
+        assert_eq!(params.set("long_rope", "5"), Ok(()));
+        assert_eq!(params.long_rope, 5);
+    }
+}
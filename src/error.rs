@@ -0,0 +1,98 @@
+use std::fmt::{self, Display, Formatter};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Error shared by all solutions and the command line
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// File couldn't be read or written at all, e.g. the input or an exported picture
+    Input { path: String, message: String },
+    /// Malformed puzzle input, `line` and `column` are 1-based
+    Parse { day: usize, line: usize, column: Option<usize>, text: String, message: String },
+    /// Well-formed input which doesn't have an answer
+    Solve { day: usize, message: String },
+    /// Overridden puzzle constant which the day doesn't have or can't use
+    Param { day: usize, message: String },
+    /// Command line which doesn't make sense, e.g. an unknown day or a flag without its value
+    Usage { message: String },
+}
+
+impl Error {
+    pub fn parse(day: usize, line: usize, text: &str, message: &str) -> Error {
+        Error::Parse { day, line, column: None, text: text.to_string(), message: message.to_string() }
+    }
+
+    /// Point a parse error at a character of its line
+    pub fn at_column(self, column: usize) -> Error {
+        match self {
+            Error::Parse { day, line, text, message, .. } => Error::Parse { day, line, column: Some(column), text, message },
+            other => other,
+        }
+    }
+
+    /// Move a parse error to the given line, for parsers which only see a single line
+    pub fn at_line(self, line: usize) -> Error {
+        match self {
+            Error::Parse { day, column, text, message, .. } => Error::Parse { day, line, column, text, message },
+            other => other,
+        }
+    }
+
+    pub fn solve(day: usize, message: &str) -> Error {
+        Error::Solve { day, message: message.to_string() }
+    }
+
+    pub fn usage(message: &str) -> Error {
+        Error::Usage { message: message.to_string() }
+    }
+
+    /// Process exit code, distinct for every kind of error (1 is reserved for failed checks)
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Parse { .. } => 2,
+            Error::Input { .. } => 3,
+            Error::Solve { .. } => 4,
+            Error::Param { .. } => 5,
+            Error::Usage { .. } => 6,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Input { path, message } => write!(f, "couldn't access {}: {}", path, message),
+            Error::Parse { day, line, column: None, text, message } => {
+                write!(f, "day {}, line {}: {}\n  | {}", day, line, message, text)
+            }
+            Error::Parse { day, line, column: Some(column), text, message } => {
+                write!(f, "day {}, line {}, column {}: {}\n  | {}\n  | {}^", day, line, column, message, text, " ".repeat(column - 1))
+            }
+            Error::Solve { day, message } => write!(f, "day {}: {}", day, message),
+            Error::Param { day, message } => write!(f, "day {}: {}", day, message),
+            Error::Usage { message } => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error;
+
+    #[test]
+    fn parse_error_diagnostic() {
+        let error = Error::parse(9, 3, "X 4", "unknown direction");
+        assert_eq!(error.to_string(), "day 9, line 3: unknown direction\n  | X 4");
+        assert_eq!(error.exit_code(), 2);
+        assert_eq!(error.at_column(3).to_string(), "day 9, line 3, column 3: unknown direction\n  | X 4\n  |   ^");
+    }
+
+    #[test]
+    fn usage_error() {
+        let error = Error::usage("--day requires a value");
+        assert_eq!(error.to_string(), "--day requires a value");
+        assert_eq!(error.exit_code(), 6);
+    }
+}
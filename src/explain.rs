@@ -0,0 +1,77 @@
+use std::cell::RefCell;
+use std::fmt::{self, Display, Formatter};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// One fact justifying an answer, e.g. `deleted directory` with `d (24933642)`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Note {
+    pub key: String,
+    pub value: String,
+}
+
+impl Display for Note {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.key, self.value)
+    }
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    static NOTES: RefCell<Option<Vec<Note>>> = const { RefCell::new(None) };
+}
+
+/// Enable or disable collecting explanations of the answers, parts don't explain anything by default
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether notes are collected on this thread, parts check it before working out costly explanations
+pub fn enabled() -> bool {
+    NOTES.with(|notes| notes.borrow().is_some())
+}
+
+/// Add a note to the explanation of the part being solved on this thread, ignored when nothing is collected
+pub fn note<V: Display>(key: &str, value: V) {
+    NOTES.with(|notes| {
+        if let Some(notes) = notes.borrow_mut().as_mut() {
+            notes.push(Note { key: key.to_string(), value: value.to_string() });
+        }
+    });
+}
+
+/// Run the part and return the notes it added on this thread, none when explanations are disabled
+pub fn collect<T, F: FnOnce() -> T>(part: F) -> (T, Vec<Note>) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return (part(), vec![]);
+    }
+    record(part)
+}
+
+/// Run the part and return the notes it added on this thread, whether or not explanations are enabled
+pub fn record<T, F: FnOnce() -> T>(part: F) -> (T, Vec<Note>) {
+    let previous = NOTES.with(|notes| notes.replace(Some(vec![])));
+    let result = part();
+    let collected = NOTES.with(|notes| notes.replace(previous));
+    (result, collected.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::explain::{self, collect, note, record, Note};
+
+    #[test]
+    fn collect_notes() {
+        note("ignored", 1);
+        let (_, notes) = collect(|| note("disabled", 2));
+        assert!(notes.is_empty());
+        let (answer, notes) = record(|| {
+            note("elf", 4);
+            explain::enabled()
+        });
+        assert!(answer);
+        assert!(!explain::enabled());
+        assert_eq!(notes, vec![Note { key: "elf".to_string(), value: "4".to_string() }]);
+        assert_eq!(notes[0].to_string(), "elf: 4");
+    }
+}
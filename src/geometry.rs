@@ -0,0 +1,230 @@
+use std::fmt::{self, Display, Formatter};
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+/// Point (or vector) on a plane, `y` grows downwards as rows do in puzzle inputs
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point {
+    pub x: isize,
+    pub y: isize,
+}
+
+impl Point {
+    pub const ORIGIN: Point = Point { x: 0, y: 0 };
+
+    pub const fn new(x: isize, y: isize) -> Point {
+        Point { x, y }
+    }
+
+    /// Parse a point in the form of `498,4`
+    pub fn parse(input: &str) -> Option<Point> {
+        let (x, y) = input.split_once(',')?;
+        Some(Point::new(isize::from_str(x.trim()).ok()?, isize::from_str(y.trim()).ok()?))
+    }
+
+    /// Vector with both coordinates clamped to -1, 0 or 1, a single step towards the same direction
+    pub fn signum(self) -> Point {
+        Point::new(self.x.signum(), self.y.signum())
+    }
+
+    pub fn manhattan(self, other: Point) -> usize {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+
+    /// Distance when diagonal steps are allowed, points touching each other are 1 apart
+    pub fn chebyshev(self, other: Point) -> usize {
+        self.x.abs_diff(other.x).max(self.y.abs_diff(other.y))
+    }
+
+    /// Neighboring point in the direction
+    pub fn step(self, direction: Direction) -> Point {
+        self + direction.offset()
+    }
+}
+
+impl Add for Point {
+    type Output = Point;
+
+    fn add(self, other: Point) -> Point {
+        Point::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl AddAssign for Point {
+    fn add_assign(&mut self, other: Point) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+
+    fn sub(self, other: Point) -> Point {
+        Point::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl SubAssign for Point {
+    fn sub_assign(&mut self, other: Point) {
+        *self = *self - other;
+    }
+}
+
+impl Mul<isize> for Point {
+    type Output = Point;
+
+    fn mul(self, factor: isize) -> Point {
+        Point::new(self.x * factor, self.y * factor)
+    }
+}
+
+impl Neg for Point {
+    type Output = Point;
+
+    fn neg(self) -> Point {
+        Point::new(-self.x, -self.y)
+    }
+}
+
+impl Display for Point {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
+/// One of the 8 directions on a grid, up is towards smaller `y`
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
+}
+
+impl Direction {
+    /// The 4 directions without diagonals
+    pub const ORTHOGONAL: [Direction; 4] = [Direction::Right, Direction::Left, Direction::Up, Direction::Down];
+
+    /// All 8 directions, orthogonal ones first
+    pub const ALL: [Direction; 8] = [
+        Direction::Right,
+        Direction::Left,
+        Direction::Up,
+        Direction::Down,
+        Direction::UpRight,
+        Direction::UpLeft,
+        Direction::DownRight,
+        Direction::DownLeft,
+    ];
+
+    /// Parse a single letter direction, `U`, `D`, `L` or `R`
+    pub fn parse(input: &str) -> Option<Direction> {
+        match input {
+            "U" => Some(Direction::Up),
+            "D" => Some(Direction::Down),
+            "L" => Some(Direction::Left),
+            "R" => Some(Direction::Right),
+            _ => None,
+        }
+    }
+
+    /// Parse a move in the form of `R 4`
+    pub fn parse_move(input: &str) -> Option<(Direction, usize)> {
+        let (direction, steps) = input.split_once(' ')?;
+        Some((Direction::parse(direction)?, usize::from_str(steps).ok()?))
+    }
+
+    pub fn offset(self) -> Point {
+        match self {
+            Direction::Up => Point::new(0, -1),
+            Direction::Down => Point::new(0, 1),
+            Direction::Left => Point::new(-1, 0),
+            Direction::Right => Point::new(1, 0),
+            Direction::UpLeft => Point::new(-1, -1),
+            Direction::UpRight => Point::new(1, -1),
+            Direction::DownLeft => Point::new(-1, 1),
+            Direction::DownRight => Point::new(1, 1),
+        }
+    }
+
+    /// Direction turned by 90 degrees clockwise
+    pub fn turn_right(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Right,
+            Direction::Right => Direction::Down,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+            Direction::UpRight => Direction::DownRight,
+            Direction::DownRight => Direction::DownLeft,
+            Direction::DownLeft => Direction::UpLeft,
+            Direction::UpLeft => Direction::UpRight,
+        }
+    }
+
+    /// Direction turned by 90 degrees counterclockwise
+    pub fn turn_left(self) -> Direction {
+        self.turn_right().opposite()
+    }
+
+    pub fn opposite(self) -> Direction {
+        self.turn_right().turn_right()
+    }
+}
+
+impl Display for Direction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let arrow = match self {
+            Direction::Up => "^",
+            Direction::Down => "v",
+            Direction::Left => "<",
+            Direction::Right => ">",
+            Direction::UpLeft => "↖",
+            Direction::UpRight => "↗",
+            Direction::DownLeft => "↙",
+            Direction::DownRight => "↘",
+        };
+        write!(f, "{}", arrow)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geometry::{Direction, Point};
+
+    #[test]
+    fn point_arithmetic() {
+        let a = Point::new(3, -2);
+        let b = Point::new(-1, 4);
+        assert_eq!(a + b, Point::new(2, 2));
+        assert_eq!(a - b, Point::new(4, -6));
+        assert_eq!(-a * 2, Point::new(-6, 4));
+        assert_eq!((a - b).signum(), Point::new(1, -1));
+        assert_eq!(a.manhattan(b), 10);
+        assert_eq!(a.chebyshev(b), 6);
+        assert_eq!(Point::ORIGIN.step(Direction::UpLeft), Point::new(-1, -1));
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(Point::parse("498,4"), Some(Point::new(498, 4)));
+        assert_eq!(Point::parse("498"), None);
+        assert_eq!(Direction::parse_move("R 4"), Some((Direction::Right, 4)));
+        assert_eq!(Direction::parse_move("X 4"), None);
+        assert_eq!(Direction::parse_move("U four"), None);
+    }
+
+    #[test]
+    fn turns() {
+        for direction in Direction::ALL {
+            assert_eq!(direction.opposite().offset(), -direction.offset());
+            assert_eq!(direction.turn_left().turn_right(), direction);
+            assert_eq!(direction.turn_right().turn_right().turn_right().turn_right(), direction);
+        }
+        assert_eq!(Direction::Up.turn_right(), Direction::Right);
+        assert_eq!(Direction::UpLeft.turn_left(), Direction::DownLeft);
+    }
+}
@@ -0,0 +1,303 @@
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::ops::{Index, IndexMut};
+
+use crate::error::{Error, Result};
+use crate::geometry::{Direction, Point};
+
+/// Dense rectangular grid, `x` is the column and `y` the row from the top left corner
+#[derive(Clone, Debug, PartialEq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, cell: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid { width, height, cells: vec![cell; width * height] }
+    }
+
+    /// Parse a character map, one row per line
+    ///
+    /// `what` names the map in error messages and `cell` converts a single character, its error
+    /// is reported on the line of the character.
+    pub fn parse<F>(day: usize, input: &str, what: &str, mut cell: F) -> Result<Grid<T>>
+    where
+        F: FnMut(char) -> std::result::Result<T, &'static str>,
+    {
+        let width = input.lines().next().map(|line| line.chars().count()).unwrap_or(0);
+        if width == 0 {
+            return Err(Error::parse(day, 1, input.lines().next().unwrap_or(""), &format!("The {} is empty", what)));
+        }
+
+        let mut cells = vec![];
+        for (index, line) in input.lines().enumerate() {
+            if line.chars().count() != width {
+                return Err(Error::parse(day, index + 1, line, &format!("All rows of the {} have to be equally long", what)));
+            }
+            for character in line.chars() {
+                cells.push(cell(character).map_err(|message| Error::parse(day, index + 1, line, message))?);
+            }
+        }
+        Ok(Grid { width, height: cells.len() / width, cells })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, position: Point) -> bool {
+        self.index(position).is_some()
+    }
+
+    fn index(&self, Point { x, y }: Point) -> Option<usize> {
+        let (x, y) = (usize::try_from(x).ok()?, usize::try_from(y).ok()?);
+        if x < self.width && y < self.height {
+            Some(y * self.width + x)
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&self, position: Point) -> Option<&T> {
+        self.cells.get(self.index(position)?)
+    }
+
+    pub fn get_mut(&mut self, position: Point) -> Option<&mut T> {
+        let index = self.index(position)?;
+        self.cells.get_mut(index)
+    }
+
+    /// All positions row by row
+    pub fn positions(&self) -> impl Iterator<Item = Point> {
+        let width = self.width;
+        (0..self.cells.len()).map(move |index| Point::new((index % width) as isize, (index / width) as isize))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    /// First position (row by row) of a cell matching the predicate
+    pub fn find<P: Fn(&T) -> bool>(&self, predicate: P) -> Option<Point> {
+        self.iter().find(|(_, cell)| predicate(cell)).map(|(position, _)| position)
+    }
+
+    /// Neighboring position in the direction, `None` when it's outside of the grid
+    pub fn neighbor(&self, position: Point, direction: Direction) -> Option<Point> {
+        Some(position.step(direction)).filter(|neighbor| self.contains(*neighbor))
+    }
+
+    /// Orthogonal neighbors inside of the grid
+    pub fn neighbors_4(&self, position: Point) -> impl Iterator<Item = Point> + '_ {
+        Direction::ORTHOGONAL.into_iter().filter_map(move |direction| self.neighbor(position, direction))
+    }
+
+    /// Orthogonal and diagonal neighbors inside of the grid
+    pub fn neighbors_8(&self, position: Point) -> impl Iterator<Item = Point> + '_ {
+        Direction::ALL.into_iter().filter_map(move |direction| self.neighbor(position, direction))
+    }
+
+    pub fn row(&self, y: usize) -> impl Iterator<Item = &T> {
+        self.cells[y * self.width..(y + 1) * self.width].iter()
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        self.cells.iter().skip(x).step_by(self.width)
+    }
+
+    /// Cells from the position (excluding it) in the direction up to the edge
+    pub fn ray(&self, position: Point, direction: Direction) -> Ray<'_, T> {
+        Ray { grid: self, position, direction }
+    }
+}
+
+/// Panics when the position is outside of the grid, use [Grid::get] when it may be
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, position: Point) -> &T {
+        self.get(position).unwrap_or_else(|| panic!("Position {} is outside of the grid", position))
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, position: Point) -> &mut T {
+        self.get_mut(position).unwrap_or_else(|| panic!("Position {} is outside of the grid", position))
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for y in 0..self.height {
+            for cell in self.row(y) {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Iterator over cells in a single direction, see [Grid::ray]
+pub struct Ray<'a, T> {
+    grid: &'a Grid<T>,
+    position: Point,
+    direction: Direction,
+}
+
+impl<'a, T> Iterator for Ray<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.position = self.grid.neighbor(self.position, self.direction)?;
+        self.grid.get(self.position)
+    }
+}
+
+/// Unbounded grid storing only occupied cells, coordinates can be negative
+#[derive(Clone, Debug, Default)]
+pub struct SparseGrid<T> {
+    cells: HashMap<Point, T>,
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> SparseGrid<T> {
+        SparseGrid { cells: HashMap::new() }
+    }
+
+    pub fn get(&self, position: Point) -> Option<&T> {
+        self.cells.get(&position)
+    }
+
+    pub fn contains(&self, position: Point) -> bool {
+        self.cells.contains_key(&position)
+    }
+
+    /// Occupy the cell, returning its previous content
+    pub fn insert(&mut self, position: Point, cell: T) -> Option<T> {
+        self.cells.insert(position, cell)
+    }
+
+    /// Empty the cell, returning what it contained
+    pub fn remove(&mut self, position: Point) -> Option<T> {
+        self.cells.remove(&position)
+    }
+
+    /// Number of occupied cells
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Occupied cells in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.cells.iter().map(|(position, cell)| (*position, cell))
+    }
+
+    /// Top left and bottom right corner of the smallest rectangle containing all occupied cells
+    pub fn bounds(&self) -> Option<(Point, Point)> {
+        let xs = self.cells.keys().map(|point| point.x);
+        let ys = self.cells.keys().map(|point| point.y);
+        Some((Point::new(xs.clone().min()?, ys.clone().min()?), Point::new(xs.max()?, ys.max()?)))
+    }
+
+    /// Occupied orthogonal neighbors
+    pub fn neighbors_4(&self, position: Point) -> impl Iterator<Item = Point> + '_ {
+        Direction::ORTHOGONAL.into_iter().map(move |direction| position.step(direction)).filter(|neighbor| self.contains(*neighbor))
+    }
+
+    /// Occupied orthogonal and diagonal neighbors
+    pub fn neighbors_8(&self, position: Point) -> impl Iterator<Item = Point> + '_ {
+        Direction::ALL.into_iter().map(move |direction| position.step(direction)).filter(|neighbor| self.contains(*neighbor))
+    }
+}
+
+impl<T: Clone> From<&Grid<T>> for SparseGrid<T> {
+    fn from(grid: &Grid<T>) -> SparseGrid<T> {
+        SparseGrid { cells: grid.iter().map(|(position, cell)| (position, cell.clone())).collect() }
+    }
+}
+
+/// Renders the bounding rectangle of occupied cells, empty cells are drawn as `.`
+impl<T: Display> Display for SparseGrid<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let Some((min, max)) = self.bounds() else {
+            return Ok(());
+        };
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                match self.get(Point::new(x, y)) {
+                    Some(cell) => write!(f, "{}", cell)?,
+                    None => write!(f, ".")?,
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error;
+    use crate::geometry::{Direction, Point};
+    use crate::grid::{Grid, SparseGrid};
+
+    fn digits(input: &str) -> Grid<u32> {
+        Grid::parse(0, input, "grid", |c| c.to_digit(10).ok_or("Not a digit")).unwrap()
+    }
+
+    #[test]
+    fn parse_character_map() {
+        let grid = digits("123\n456\n");
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid.get(Point::new(2, 1)), Some(&6));
+        assert_eq!(grid[Point::new(0, 1)], 4);
+        assert_eq!(grid.get(Point::new(3, 1)), None);
+        assert_eq!(grid.get(Point::new(-1, 0)), None);
+        assert_eq!(grid.row(1).copied().collect::<Vec<u32>>(), vec![4, 5, 6]);
+        assert_eq!(grid.column(1).copied().collect::<Vec<u32>>(), vec![2, 5]);
+        assert_eq!(grid.to_string(), "123\n456\n");
+
+        let parse = |input| Grid::parse(0, input, "grid", |c| c.to_digit(10).ok_or("Not a digit"));
+        assert_eq!(parse("12\n3").err(), Some(Error::parse(0, 2, "3", "All rows of the grid have to be equally long")));
+        assert_eq!(parse("12\n3x").err(), Some(Error::parse(0, 2, "3x", "Not a digit")));
+        assert_eq!(parse("").err(), Some(Error::parse(0, 1, "", "The grid is empty")));
+    }
+
+    #[test]
+    fn neighbors_and_rays() {
+        let grid = digits("123\n456\n789");
+        assert_eq!(grid.neighbors_4(Point::ORIGIN).collect::<Vec<Point>>(), vec![Point::new(1, 0), Point::new(0, 1)]);
+        assert_eq!(grid.neighbors_8(Point::ORIGIN).count(), 3);
+        assert_eq!(grid.neighbors_8(Point::new(1, 1)).count(), 8);
+        assert_eq!(grid.ray(Point::new(1, 1), Direction::Right).copied().collect::<Vec<u32>>(), vec![6]);
+        assert_eq!(grid.ray(Point::new(2, 2), Direction::UpLeft).copied().collect::<Vec<u32>>(), vec![5, 1]);
+        assert_eq!(grid.find(|cell| *cell == 8), Some(Point::new(1, 2)));
+    }
+
+    #[test]
+    fn sparse_grid() {
+        let mut grid = SparseGrid::new();
+        grid.insert(Point::new(-1, 0), '#');
+        grid.insert(Point::new(1, 1), 'o');
+        assert_eq!(grid.bounds(), Some((Point::new(-1, 0), Point::new(1, 1))));
+        assert_eq!(grid.to_string(), "#..\n..o\n");
+        assert_eq!(grid.neighbors_8(Point::ORIGIN).count(), 2);
+
+        let dense = SparseGrid::from(&digits("12\n34"));
+        assert_eq!(dense.to_string(), "12\n34\n");
+    }
+}
@@ -0,0 +1,212 @@
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::grid::{Grid, SparseGrid};
+
+/// Picture of a puzzle, values from 0 to 1 are colored by a palette and `None` is the background
+pub type Picture = Grid<Option<f64>>;
+
+/// Picture of the bounding rectangle of a sparse grid, with the value of every occupied cell
+pub fn from_sparse<T, F: Fn(&T) -> f64>(grid: &SparseGrid<T>, value: F) -> Picture {
+    let Some((min, max)) = grid.bounds() else {
+        return Grid::new(0, 0, None);
+    };
+    let mut picture = Grid::new((max.x - min.x + 1) as usize, (max.y - min.y + 1) as usize, None);
+    for (position, cell) in grid.iter() {
+        picture[position - min] = Some(value(cell));
+    }
+    picture
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Color {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+}
+
+impl Color {
+    pub const fn new(red: u8, green: u8, blue: u8) -> Color {
+        Color { red, green, blue }
+    }
+
+    /// Parse a color in the form of `#ff8000`
+    pub fn parse(input: &str) -> Option<Color> {
+        let hex = input.strip_prefix('#').filter(|hex| hex.len() == 6 && hex.is_ascii())?;
+        let channel = |index: usize| u8::from_str_radix(&hex[index..index + 2], 16).ok();
+        Some(Color::new(channel(0)?, channel(2)?, channel(4)?))
+    }
+
+    fn mix(self, other: Color, ratio: f64) -> Color {
+        let channel = |from: u8, to: u8| (from as f64 + (to as f64 - from as f64) * ratio).round() as u8;
+        Color::new(channel(self.red, other.red), channel(self.green, other.green), channel(self.blue, other.blue))
+    }
+}
+
+impl Display for Color {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.red, self.green, self.blue)
+    }
+}
+
+/// Gradient through evenly spaced colors
+#[derive(Clone, Debug, PartialEq)]
+pub struct Palette {
+    stops: Vec<Color>,
+}
+
+impl Palette {
+    /// One of the named palettes (`heat`, `gray`, `ocean`) or a list of colors like `#000000,#ff0000`
+    pub fn parse(input: &str) -> Option<Palette> {
+        let stops = match input {
+            "heat" => vec![Color::new(0, 0, 0), Color::new(200, 30, 0), Color::new(255, 200, 0), Color::new(255, 255, 255)],
+            "gray" => vec![Color::new(0, 0, 0), Color::new(255, 255, 255)],
+            "ocean" => vec![Color::new(8, 24, 88), Color::new(30, 140, 160), Color::new(220, 245, 240)],
+            colors => colors.split(',').map(Color::parse).collect::<Option<Vec<Color>>>()?,
+        };
+        Some(Palette { stops }).filter(|palette| !palette.stops.is_empty())
+    }
+
+    /// Color of a value from 0 to 1, values outside of the range are clamped
+    pub fn color(&self, value: f64) -> Color {
+        let position = value.clamp(0.0, 1.0) * (self.stops.len() - 1) as f64;
+        let index = (position.floor() as usize).min(self.stops.len() - 1);
+        match self.stops.get(index + 1) {
+            Some(next) => self.stops[index].mix(*next, position - index as f64),
+            None => self.stops[index],
+        }
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Palette {
+        Palette::parse("heat").unwrap()
+    }
+}
+
+/// How a picture is turned into an image
+#[derive(Clone, Debug)]
+pub struct Style {
+    pub palette: Palette,
+    pub background: Color,
+    /// Width and height of a single cell in pixels
+    pub cell_size: usize,
+}
+
+impl Default for Style {
+    fn default() -> Style {
+        Style { palette: Palette::default(), background: Color::new(24, 24, 24), cell_size: 4 }
+    }
+}
+
+impl Style {
+    fn color(&self, value: Option<f64>) -> Color {
+        value.map_or(self.background, |value| self.palette.color(value))
+    }
+}
+
+/// Binary PPM (P6) image
+pub fn ppm(picture: &Picture, style: &Style) -> Vec<u8> {
+    let size = style.cell_size;
+    let mut image = format!("P6\n{} {}\n255\n", picture.width() * size, picture.height() * size).into_bytes();
+    for y in 0..picture.height() {
+        let row = picture.row(y).flat_map(|value| {
+            let color = style.color(*value);
+            [color.red, color.green, color.blue].repeat(size)
+        });
+        let row = row.collect::<Vec<u8>>();
+        for _ in 0..size {
+            image.extend_from_slice(&row);
+        }
+    }
+    image
+}
+
+/// SVG image, neighboring cells of the same color in a row are merged into one rectangle
+pub fn svg(picture: &Picture, style: &Style) -> String {
+    let size = style.cell_size;
+    let (width, height) = (picture.width() * size, picture.height() * size);
+    let mut image = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\" shape-rendering=\"crispEdges\">\n",
+        width, height, width, height
+    );
+    image.push_str(&format!("<rect width=\"{}\" height=\"{}\" fill=\"{}\"/>\n", width, height, style.background));
+    for y in 0..picture.height() {
+        let row = picture.row(y).copied().collect::<Vec<Option<f64>>>();
+        let mut start = 0;
+        while start < row.len() {
+            let color = style.color(row[start]);
+            let length = row[start..].iter().take_while(|value| style.color(**value) == color).count();
+            if row[start].is_some() {
+                image.push_str(&format!(
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+                    start * size,
+                    y * size,
+                    length * size,
+                    size,
+                    color
+                ));
+            }
+            start += length;
+        }
+    }
+    image.push_str("</svg>\n");
+    image
+}
+
+/// Write the picture as PPM or SVG, depending on the extension of the file
+pub fn export(path: &Path, picture: &Picture, style: &Style) -> io::Result<()> {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("ppm") => fs::write(path, ppm(picture, style)),
+        Some("svg") => fs::write(path, svg(picture, style)),
+        _ => Err(io::Error::new(io::ErrorKind::InvalidInput, "exported file has to end with .ppm or .svg")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geometry::Point;
+    use crate::grid::{Grid, SparseGrid};
+    use crate::image::{from_sparse, ppm, svg, Color, Palette, Style};
+
+    #[test]
+    fn palettes() {
+        let palette = Palette::parse("#000000,#ff0000,#ffffff").unwrap();
+        assert_eq!(palette.color(0.0), Color::new(0, 0, 0));
+        assert_eq!(palette.color(0.25), Color::new(128, 0, 0));
+        assert_eq!(palette.color(0.75), Color::new(255, 128, 128));
+        assert_eq!(palette.color(2.0), Color::new(255, 255, 255));
+        assert_eq!(Palette::parse("#00000"), None);
+        assert_eq!(Palette::parse("gray").unwrap().color(0.5).to_string(), "#808080");
+    }
+
+    #[test]
+    fn encode_images() {
+        let mut picture = Grid::new(3, 1, None);
+        picture[Point::new(1, 0)] = Some(1.0);
+        picture[Point::new(2, 0)] = Some(1.0);
+        let style = Style { palette: Palette::parse("gray").unwrap(), background: Color::new(0, 0, 255), cell_size: 2 };
+
+        let image = ppm(&picture, &style);
+        assert!(image.starts_with(b"P6\n6 2\n255\n"));
+        assert_eq!(image.len(), 11 + 6 * 2 * 3);
+        assert_eq!(&image[11..17], &[0, 0, 255, 0, 0, 255]);
+
+        let image = svg(&picture, &style);
+        assert!(image.contains("<rect x=\"2\" y=\"0\" width=\"4\" height=\"2\" fill=\"#ffffff\"/>"));
+        assert_eq!(image.matches("<rect").count(), 2);
+    }
+
+    #[test]
+    fn sparse_picture() {
+        let mut grid = SparseGrid::new();
+        grid.insert(Point::new(-1, 5), 2);
+        grid.insert(Point::new(1, 6), 4);
+        let picture = from_sparse(&grid, |value| *value as f64 / 4.0);
+        assert_eq!((picture.width(), picture.height()), (3, 2));
+        assert_eq!(picture.row(0).copied().collect::<Vec<Option<f64>>>(), vec![Some(0.5), None, None]);
+        assert_eq!(picture[Point::new(2, 1)], Some(1.0));
+    }
+}
@@ -0,0 +1,147 @@
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+
+use crate::error::{Error, Result};
+
+const RESOURCES_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/resources");
+
+/// Load puzzle input at runtime
+///
+/// `path` is either a file path or `-` for stdin. When no path is given, `default_file` is read
+/// from the resources directory instead. In `strict` mode input which [normalize] would have to
+/// fix is rejected, listing the fixes.
+pub fn load(path: Option<&str>, default_file: &str, strict: bool) -> Result<String> {
+    let (name, input) = match path {
+        Some("-") => {
+            let mut input = String::new();
+            io::stdin()
+                .read_to_string(&mut input)
+                .map_err(|err| Error::Input { path: "stdin".to_string(), message: err.to_string() })?;
+            ("stdin".to_string(), input)
+        }
+        Some(path) => (path.to_string(), read(Path::new(path))?),
+        None => {
+            let path = Path::new(RESOURCES_DIR).join(default_file);
+            (path.display().to_string(), read(&path)?)
+        }
+    };
+
+    let (_, fixes) = normalize(&input);
+    if strict && !fixes.is_empty() {
+        let fixes = fixes.iter().map(Fix::to_string).collect::<Vec<String>>().join(", ");
+        return Err(Error::Input { path: name, message: format!("strict mode doesn't allow {}", fixes) });
+    }
+    Ok(input)
+}
+
+fn read(path: &Path) -> Result<String> {
+    fs::read_to_string(path).map_err(|err| Error::Input { path: path.display().to_string(), message: err.to_string() })
+}
+
+/// Difference between the input as written and what the solutions expect
+#[derive(Debug, PartialEq)]
+pub enum Fix {
+    ByteOrderMark,
+    /// Number of lines ending with `\r\n`
+    CarriageReturns(usize),
+    /// Number of lines containing tabs
+    Tabs(usize),
+    TrailingBlankLines(usize),
+    MissingFinalNewline,
+}
+
+impl Display for Fix {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Fix::ByteOrderMark => write!(f, "byte order mark"),
+            Fix::CarriageReturns(lines) => write!(f, "CRLF line endings on {} line(s)", lines),
+            Fix::Tabs(lines) => write!(f, "tabs on {} line(s)", lines),
+            Fix::TrailingBlankLines(lines) => write!(f, "{} trailing blank line(s)", lines),
+            Fix::MissingFinalNewline => write!(f, "missing final newline"),
+        }
+    }
+}
+
+/// Input every solution can rely on, together with what had to be fixed to get it
+///
+/// Lines end with a single `\n` (including the last one), there is no byte order mark, no blank
+/// lines at the end and tabs are replaced by single spaces, which is how puzzles separate fields.
+/// Everything else, including spaces at the end of lines, is kept as it is.
+pub fn normalize(input: &str) -> (String, Vec<Fix>) {
+    let mut fixes = vec![];
+    let input = match input.strip_prefix('\u{feff}') {
+        Some(input) => {
+            fixes.push(Fix::ByteOrderMark);
+            input
+        }
+        None => input,
+    };
+
+    let mut lines = input.split('\n').collect::<Vec<&str>>();
+    if lines.last() == Some(&"") {
+        lines.pop();
+    } else if !input.is_empty() {
+        fixes.push(Fix::MissingFinalNewline);
+    }
+
+    let (mut carriage_returns, mut tabs) = (0, 0);
+    let mut lines = lines
+        .into_iter()
+        .map(|line| {
+            let line = line.strip_suffix('\r').inspect(|_| carriage_returns += 1).unwrap_or(line);
+            if line.contains('\t') {
+                tabs += 1;
+                line.replace('\t', " ")
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<String>>();
+    if carriage_returns > 0 {
+        fixes.push(Fix::CarriageReturns(carriage_returns));
+    }
+    if tabs > 0 {
+        fixes.push(Fix::Tabs(tabs));
+    }
+
+    let blank = lines.iter().rev().take_while(|line| line.trim().is_empty()).count();
+    if blank > 0 {
+        lines.truncate(lines.len() - blank);
+        // a missing newline after blank lines isn't worth mentioning on its own
+        fixes.retain(|fix| *fix != Fix::MissingFinalNewline);
+        fixes.push(Fix::TrailingBlankLines(blank));
+    }
+
+    let text = lines.iter().map(|line| format!("{}\n", line)).collect();
+    (text, fixes)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error;
+    use crate::input::{load, normalize, Fix};
+
+    #[test]
+    fn load_default_resource() {
+        let input = load(None, "test/day_2_rock_paper_scissors_example.txt", true).unwrap();
+        assert_eq!(input, include_str!("resources/test/day_2_rock_paper_scissors_example.txt"));
+    }
+
+    #[test]
+    fn load_missing_file() {
+        assert!(matches!(load(Some("does/not/exist.txt"), "", false), Err(Error::Input { .. })));
+    }
+
+    #[test]
+    fn normalize_windows_file() {
+        let (text, fixes) = normalize("\u{feff}A Y\r\nB\tX\r\n\r\n  \n");
+        assert_eq!(text, "A Y\nB X\n");
+        assert_eq!(fixes, vec![Fix::ByteOrderMark, Fix::CarriageReturns(3), Fix::Tabs(1), Fix::TrailingBlankLines(2)]);
+
+        assert_eq!(normalize("1\n\n2"), ("1\n\n2\n".to_string(), vec![Fix::MissingFinalNewline]));
+        assert_eq!(normalize("    [D]    \n"), ("    [D]    \n".to_string(), vec![]));
+        assert_eq!(normalize(""), (String::new(), vec![]));
+    }
+}
@@ -0,0 +1,122 @@
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use crate::error::Result;
+use crate::params::Overrides;
+use crate::report::format_duration;
+use crate::solution::{Run, Runnable};
+
+/// How a solution running on its own thread ended, errors of the solution are returned as errors
+pub enum Outcome {
+    Solved(Run),
+    /// The solution didn't finish within the time limit, its thread keeps running in the background
+    Timeout(Duration),
+    Panic(String),
+}
+
+impl Outcome {
+    /// Result row of a solution which didn't produce answers, `None` when it did
+    pub fn failure(&self) -> Option<String> {
+        match self {
+            Outcome::Solved(_) => None,
+            Outcome::Timeout(limit) => Some(format!("TIMEOUT after {}", format_duration(*limit))),
+            Outcome::Panic(message) => Some(format!("PANIC: {}", message)),
+        }
+    }
+}
+
+/// Solve both parts on a separate thread, so a panic or an endless loop doesn't take down the caller
+///
+/// There is no way to stop a thread, so after a timeout it's left running until the process ends.
+pub fn run(solution: &'static dyn Runnable, input: String, overrides: Overrides, limit: Option<Duration>) -> Result<Outcome> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let result = panic::catch_unwind(AssertUnwindSafe(|| solution.run(&input, &overrides)));
+        // the receiver is gone when it stopped waiting after a timeout
+        let _ = sender.send(result);
+    });
+    let received = match limit {
+        Some(limit) => receiver.recv_timeout(limit),
+        None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+    };
+    match received {
+        Ok(Ok(run)) => run.map(Outcome::Solved),
+        Ok(Err(payload)) => Ok(Outcome::Panic(panic_message(payload))),
+        Err(RecvTimeoutError::Timeout) => Ok(Outcome::Timeout(limit.unwrap_or_default())),
+        Err(RecvTimeoutError::Disconnected) => Ok(Outcome::Panic("solution thread ended without an answer".to_string())),
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload.downcast_ref::<&str>().map_or("unknown panic".to_string(), |message| message.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::error::Result;
+    use crate::isolate::{run, Outcome};
+    use crate::params::Overrides;
+    use crate::random::Random;
+    use crate::solution::{find, Solution};
+
+    /// Day which panics on input `panic` and never finishes on input `loop`
+    struct Broken;
+
+    impl Solution for Broken {
+        type Input = String;
+        type Params = ();
+
+        fn name(&self) -> &'static str {
+            "Broken"
+        }
+
+        fn day(&self) -> usize {
+            0
+        }
+
+        fn input(&self) -> &'static str {
+            "broken.txt"
+        }
+
+        fn parse(&self, input: &str) -> Result<String> {
+            Ok(input.to_string())
+        }
+
+        fn part1(&self, input: &String, _params: &()) -> Result<String> {
+            match input.trim() {
+                "panic" => panic!("index {} out of range", 3),
+                "loop" => loop {
+                    std::thread::sleep(Duration::from_millis(10));
+                },
+                input => Ok(input.to_string()),
+            }
+        }
+
+        fn part2(&self, input: &String, _params: &()) -> Result<String> {
+            Ok(input.to_string())
+        }
+
+        fn generate(&self, _random: &mut Random, _size: usize) -> String {
+            String::new()
+        }
+    }
+
+    #[test]
+    fn timeouts_and_panics() {
+        let limit = Some(Duration::from_millis(50));
+        let Ok(Outcome::Solved(solved)) = run(find(1).unwrap(), "1\n\n2\n".to_string(), Overrides::default(), limit) else {
+            panic!("day 1 didn't solve its input");
+        };
+        assert_eq!(solved.parts[0].answer, "2");
+        assert_eq!(run(&Broken, "panic".to_string(), Overrides::default(), limit).unwrap().failure(), Some("PANIC: index 3 out of range".to_string()));
+        assert_eq!(run(&Broken, "loop".to_string(), Overrides::default(), limit).unwrap().failure(), Some("TIMEOUT after 50.000ms".to_string()));
+    }
+}
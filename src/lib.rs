@@ -0,0 +1,51 @@
+//! Solutions of [Advent of Code 2022](https://adventofcode.com/2022) puzzles
+//!
+//! Every day lives in its own module with a unit struct implementing [solution::Solution] and
+//! the model types and solve functions behind it, so they can be used on their own:
+//!
+//! ```
+//! use advent_of_code_2022::day_13::{Packet, Status};
+//!
+//! let left = Packet::parse("[1,[2,[3]]]").unwrap();
+//! let right = Packet::parse("[1,[2,[4]]]").unwrap();
+//! assert!(left.compare(&right) == Status::Ok);
+//! ```
+//!
+//! All days are also available through the [solution::SOLUTIONS] registry, which is what the
+//! command line uses.
+
+pub mod batch;
+pub mod bench;
+pub mod checkpoint;
+pub mod error;
+pub mod explain;
+pub mod geometry;
+pub mod grid;
+pub mod image;
+pub mod input;
+pub mod isolate;
+pub mod log;
+pub mod params;
+pub mod parse;
+pub mod random;
+pub mod search;
+pub mod simulation;
+pub mod report;
+pub mod solution;
+pub mod trace;
+pub mod verify;
+pub mod visualize;
+pub mod day_1;
+pub mod day_2;
+pub mod day_3;
+pub mod day_4;
+pub mod day_5;
+pub mod day_6;
+pub mod day_7;
+pub mod day_8;
+pub mod day_9;
+pub mod day_10;
+pub mod day_11;
+pub mod day_12;
+pub mod day_13;
+pub mod day_14;
@@ -0,0 +1,101 @@
+use std::fmt::{self, Display, Formatter};
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// How much diagnostic output is written to stderr, each level includes the ones before it
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    /// Why the program failed
+    Error,
+    /// What the program did besides solving, e.g. resuming a checkpoint
+    Info,
+    /// Pictures of the solved puzzles, e.g. the forest or the climbed path
+    Debug,
+    /// Every step of the solutions, e.g. each compared pair of packets
+    Trace,
+}
+
+/// Name of the environment variable with the level, e.g. `AOC_LOG=debug`
+pub const VARIABLE: &str = "AOC_LOG";
+
+impl Level {
+    pub fn parse(level: &str) -> Option<Level> {
+        match level.trim().to_lowercase().as_str() {
+            "error" => Some(Level::Error),
+            "info" => Some(Level::Info),
+            "debug" => Some(Level::Debug),
+            "trace" => Some(Level::Trace),
+            _ => None,
+        }
+    }
+
+    /// Level of the given number of `-v` flags on top of the default one
+    pub fn verbose(count: usize) -> Level {
+        match count {
+            0 => Level::Info,
+            1 => Level::Debug,
+            _ => Level::Trace,
+        }
+    }
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Level::Error => "error",
+            Level::Info => "info",
+            Level::Debug => "debug",
+            Level::Trace => "trace",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+static LEVEL: AtomicU8 = AtomicU8::new(Level::Info as u8);
+
+/// Set the most detailed level which is written, messages of the ones after it are dropped
+pub fn set_level(level: Level) {
+    LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+/// Whether messages of the level are written, check it before building costly ones
+pub fn enabled(level: Level) -> bool {
+    level as u8 <= LEVEL.load(Ordering::Relaxed)
+}
+
+/// Write the message to stderr prefixed by its level, multi-line messages continue on the following lines
+pub fn log<M: Display>(level: Level, message: M) {
+    if enabled(level) {
+        eprintln!("{}: {}", level, message.to_string().trim_end());
+    }
+}
+
+pub fn error<M: Display>(message: M) {
+    log(Level::Error, message);
+}
+
+pub fn info<M: Display>(message: M) {
+    log(Level::Info, message);
+}
+
+pub fn debug<M: Display>(message: M) {
+    log(Level::Debug, message);
+}
+
+pub fn trace<M: Display>(message: M) {
+    log(Level::Trace, message);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::log::Level;
+
+    #[test]
+    fn parse_levels() {
+        assert_eq!(Level::parse(" Debug\n"), Some(Level::Debug));
+        assert_eq!(Level::parse("verbose"), None);
+        assert_eq!(Level::verbose(0), Level::Info);
+        assert_eq!(Level::verbose(3), Level::Trace);
+        assert!(Level::Error < Level::Trace);
+        assert_eq!(Level::Trace.to_string(), "trace");
+    }
+}
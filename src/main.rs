@@ -1,41 +1,355 @@
 use std::env;
+use std::fs;
+use std::path::Path;
+use std::process;
 use std::str::FromStr;
+use std::time::Duration;
 
-mod day_1;
-mod day_2;
-mod day_3;
-mod day_4;
-mod day_5;
-mod day_6;
-mod day_7;
-mod day_8;
-mod day_9;
-mod day_10;
-mod day_11;
-mod day_12;
-mod day_13;
-mod day_14;
+use advent_of_code_2022::batch;
+use advent_of_code_2022::checkpoint;
+use advent_of_code_2022::error::{Error, Result};
+use advent_of_code_2022::image::{self, Palette, Style};
+use advent_of_code_2022::isolate::{self, Outcome};
+use advent_of_code_2022::params::Overrides;
+use advent_of_code_2022::random::Random;
+use advent_of_code_2022::report::{self, Format};
+use advent_of_code_2022::solution::{self, Runnable, SOLUTIONS};
+use advent_of_code_2022::trace::{self, Trace};
+use advent_of_code_2022::visualize::{self, Player, Viewport};
+use advent_of_code_2022::log::{self, Level};
+use advent_of_code_2022::{bench, explain, input, verify};
+
+struct Args {
+    command: String,
+    positional: Vec<String>,
+    input: Option<String>,
+    day: Option<usize>,
+    inputs: Option<String>,
+    format: Format,
+    answers: String,
+    iterations: usize,
+    save: Option<String>,
+    baseline: Option<String>,
+    threshold: f64,
+    size: usize,
+    seed: Option<u64>,
+    strict: bool,
+    /// Diagnostics written to stderr, from the environment variable unless `-v` flags were given
+    log_level: Level,
+    /// Print the notes justifying the answers of the days which have them
+    explain: bool,
+    /// Puzzle constants from the config file, overridden by the ones given with `--param`
+    params: Overrides,
+    /// Time limit of a single solution, `None` waits as long as it takes
+    timeout: Option<Duration>,
+    speed: f64,
+    viewport: Viewport,
+    paused: bool,
+    export: Option<String>,
+    style: Style,
+    checkpoint: checkpoint::Settings,
+}
+
+impl Args {
+    fn parse() -> Result<Args> {
+        let mut positional = vec![];
+        let mut args = Args {
+            command: String::new(),
+            positional: vec![],
+            input: None,
+            day: None,
+            inputs: None,
+            format: Format::Text,
+            answers: String::from("answers.txt"),
+            iterations: 10,
+            save: None,
+            baseline: None,
+            threshold: 10.0,
+            size: 100,
+            seed: None,
+            strict: false,
+            explain: false,
+            log_level: env::var(log::VARIABLE).map_or(Level::Info, |level| {
+                Level::parse(&level).unwrap_or_else(|| {
+                    log::error(format_args!("unknown level '{}' in {}, use error, info, debug or trace, falling back to info", level, log::VARIABLE));
+                    Level::Info
+                })
+            }),
+            params: Overrides::default(),
+            timeout: Some(Duration::from_secs(60)),
+            speed: 10.0,
+            viewport: Viewport { width: 80, height: 24 },
+            paused: false,
+            export: None,
+            style: Style::default(),
+            checkpoint: checkpoint::Settings::default(),
+        };
+
+        let mut params = Overrides::default();
+        let mut verbose = 0;
+        let mut raw = env::args().skip(1);
+        while let Some(arg) = raw.next() {
+            let mut value = || raw.next().ok_or_else(|| Error::usage(&format!("{} requires a value", arg)));
+            match arg.as_str() {
+                "--input" => args.input = Some(value()?),
+                "--day" => args.day = Some(value()?.parse().map_err(|_| Error::usage("Couldn't parse day as an unsigned number"))?),
+                "--inputs" => args.inputs = Some(value()?),
+                "--format" => args.format = Format::parse(&value()?).ok_or_else(|| Error::usage("Unknown format, use text or json"))?,
+                "--answers" => args.answers = value()?,
+                "--iterations" => args.iterations = value()?.parse().ok().filter(|iterations| *iterations > 0).ok_or_else(|| Error::usage("Number of iterations has to be a positive number"))?,
+                "--save" => args.save = Some(value()?),
+                "--baseline" => args.baseline = Some(value()?),
+                "--threshold" => args.threshold = value()?.parse().map_err(|_| Error::usage("Couldn't parse threshold percentage"))?,
+                "--size" => args.size = value()?.parse().map_err(|_| Error::usage("Couldn't parse input size"))?,
+                "--strict" => args.strict = true,
+                "--explain" => args.explain = true,
+                "--verbose" => verbose += 1,
+                flag if flag.len() > 1 && flag.strip_prefix('-').is_some_and(|flags| flags.chars().all(|flag| flag == 'v')) => verbose += flag.len() - 1,
+                "--param" => params.add(&value()?).map_err(|message| Error::usage(&message))?,
+                "--config" => {
+                    let path = value()?;
+                    let config = fs::read_to_string(&path).map_err(|err| Error::Input { path: path.clone(), message: err.to_string() })?;
+                    args.params.extend(Overrides::parse(&config).map_err(|message| Error::Input { path, message })?);
+                }
+                "--timeout" => {
+                    let seconds: f64 = value()?.parse().ok().filter(|seconds: &f64| *seconds >= 0.0).ok_or_else(|| Error::usage("Timeout has to be a number of seconds, 0 for no limit"))?;
+                    args.timeout = Some(Duration::from_secs_f64(seconds)).filter(|timeout| !timeout.is_zero());
+                }
+                "--paused" => args.paused = true,
+                "--export" => args.export = Some(value()?),
+                "--palette" => args.style.palette = Palette::parse(&value()?).ok_or_else(|| Error::usage("Unknown palette, use heat, gray, ocean or colors like #000000,#ff0000"))?,
+                "--cell-size" => args.style.cell_size = value()?.parse().ok().filter(|size| *size > 0).ok_or_else(|| Error::usage("Cell size has to be a positive number of pixels"))?,
+                "--speed" => args.speed = value()?.parse().ok().filter(|speed: &f64| *speed > 0.0).ok_or_else(|| Error::usage("Speed has to be a positive number of steps per second"))?,
+                "--viewport" => args.viewport = Viewport::parse(&value()?).ok_or_else(|| Error::usage("Viewport has to be in the form of 80x24"))?,
+                "--checkpoint" => args.checkpoint.path = Some(value()?.into()),
+                "--checkpoint-every" => args.checkpoint.interval = value()?.parse().ok().filter(|steps| *steps > 0).ok_or_else(|| Error::usage("Checkpoint interval has to be a positive number of steps"))?,
+                "--resume" => args.checkpoint.resume = Some(value()?.into()),
+                "--seed" => args.seed = Some(value()?.parse().map_err(|_| Error::usage("Couldn't parse seed as an unsigned number"))?),
+                _ => positional.push(arg),
+            }
+        }
+
+        args.params.extend(params);
+        if verbose > 0 {
+            args.log_level = Level::verbose(verbose);
+        }
+
+        if positional.is_empty() {
+            return Err(Error::usage("You have to provide a number to run a solution for given day, or a command (all, run, verify, bench, generate, animate, trace, trace-diff)!"));
+        }
+        args.command = positional.remove(0);
+        args.positional = positional;
+        Ok(args)
+    }
+
+    /// Solutions selected by the positional day numbers, all of them when none were given
+    fn solutions(&self) -> Result<Vec<&'static dyn Runnable>> {
+        if self.positional.is_empty() {
+            return Ok(SOLUTIONS.to_vec());
+        }
+        self.positional.iter().map(|day| find(day)).collect()
+    }
+
+    /// The only solution selected by the positional day numbers, `command` needs exactly one
+    fn solution(&self, command: &str) -> Result<&'static dyn Runnable> {
+        match self.solutions()?[..] {
+            [solution] if self.positional.len() == 1 => Ok(solution),
+            _ => Err(Error::usage(&format!("{} needs exactly one day number!", command))),
+        }
+    }
+}
+
+/// Solution of the day given on the command line
+fn find(day: &str) -> Result<&'static dyn Runnable> {
+    let number = usize::from_str(day).map_err(|_| Error::usage(&format!("'{}' is neither a day number nor a command", day)))?;
+    solution::find(number).ok_or_else(|| Error::usage(&format!("No solution found for day {}!", number)))
+}
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    match usize::from_str(args
-        .get(1)
-        .expect("You have to provide a number to run a solution for given day!")
-    ).expect("Provided argument couldn't be parsed as an unsigned number") {
-        1 => day_1::main(),
-        2 => day_2::main(),
-        3 => day_3::main(),
-        4 => day_4::main(),
-        5 => day_5::main(),
-        6 => day_6::main(),
-        7 => day_7::main(),
-        8 => day_8::main(),
-        9 => day_9::main(),
-        10 => day_11::main(),
-        11 => day_11::main(),
-        12 => day_12::main(),
-        13 => day_13::main(),
-        14 => day_14::main(),
-        _ => panic!("No solution found for given day number!")
+    let result = Args::parse().and_then(|args| {
+        log::set_level(args.log_level);
+        explain::set_enabled(args.explain);
+        checkpoint::configure(args.checkpoint.clone());
+        execute(&args)
+    });
+
+    if let Err(err) = result {
+        log::error(&err);
+        process::exit(err.exit_code());
+    }
+}
+
+fn execute(args: &Args) -> Result<()> {
+    match args.command.as_str() {
+        "all" => {
+            let runs = run_all(args);
+            output(args, &runs);
+            exit_on_failure(&runs);
+        }
+        "run" => run_batch(args)?,
+        "verify" => {
+            let error = |message: String| Error::Input { path: args.answers.clone(), message };
+            let expected = verify::Answers::parse(&fs::read_to_string(&args.answers).map_err(|err| error(err.to_string()))?).map_err(error)?;
+            if !verify::verify(&run_all(args), &expected) {
+                process::exit(1);
+            }
+        }
+        "bench" => bench(args)?,
+        "generate" => generate(args)?,
+        "animate" => animate(args)?,
+        "trace" => print_trace(args)?,
+        "trace-diff" => trace_diff(args)?,
+        day => {
+            let solution = find(day)?;
+            let input = input::load(args.input.as_deref(), solution.input(), args.strict)?;
+            let runs = vec![(solution, Ok(isolate::run(solution, input.clone(), args.params.clone(), args.timeout)?))];
+            output(args, &runs);
+            exit_on_failure(&runs);
+            if let Some(path) = &args.export {
+                let picture = solution.export(&input, &args.params)?.ok_or_else(|| Error::usage(&format!("Day {} has no picture to export!", solution.day())))?;
+                image::export(Path::new(path), &picture, &args.style).map_err(|err| Error::Input { path: path.clone(), message: err.to_string() })?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Run every day with the time limit, a day which fails, times out or panics doesn't stop the rest
+fn run_all(args: &Args) -> Vec<(&'static dyn Runnable, Result<Outcome>)> {
+    SOLUTIONS
+        .iter()
+        .map(|solution| {
+            let outcome = input::load(None, solution.input(), args.strict).and_then(|input| isolate::run(*solution, input, args.params.clone(), args.timeout));
+            (*solution, outcome)
+        })
+        .collect()
+}
+
+/// Exit with 1 once the results are printed, when any of the solutions failed, timed out or panicked
+fn exit_on_failure(runs: &[(&dyn Runnable, Result<Outcome>)]) {
+    if runs.iter().any(|(_, outcome)| outcome.as_ref().map_or(true, |outcome| outcome.failure().is_some())) {
+        process::exit(1);
+    }
+}
+
+/// Run one day on every file of a directory, the inputs are solved in parallel
+fn run_batch(args: &Args) -> Result<()> {
+    let day = args.day.ok_or_else(|| Error::usage("Run needs a day, e.g. run --day 5 --inputs inputs/"))?;
+    let solution = find(&day.to_string())?;
+    let directory = args.inputs.as_ref().ok_or_else(|| Error::usage("Run needs a directory of inputs, e.g. run --day 5 --inputs inputs/"))?;
+    if args.checkpoint.path.is_some() || args.checkpoint.resume.is_some() {
+        return Err(Error::usage("Run solves the inputs in parallel, checkpoints can only be used when solving a single input"));
+    }
+    let batches = batch::run(solution, &batch::inputs(Path::new(directory))?, &args.params, args.strict, args.timeout);
+    match args.format {
+        Format::Json => report::print_batch_json(day, &batches),
+        Format::Text => report::print_batch(&batches),
+    }
+    if batches.iter().any(|batch| batch.run.as_ref().map_or(true, |outcome| outcome.failure().is_some())) {
+        process::exit(1);
+    }
+    Ok(())
+}
+
+fn output(args: &Args, runs: &[(&dyn Runnable, Result<Outcome>)]) {
+    match args.format {
+        Format::Json => report::print_json(runs),
+        Format::Text if args.command == "all" => report::print_table(runs),
+        Format::Text => {
+            let (solution, outcome) = &runs[0];
+            println!("Day {}: {}", solution.day(), solution.name());
+            match outcome {
+                Ok(Outcome::Solved(run)) => {
+                    for (index, part) in run.parts.iter().enumerate() {
+                        print_answer(index + 1, &part.answer);
+                        report::print_explanation(&part.explanation);
+                    }
+                }
+                Ok(failed) => println!("{}", failed.failure().unwrap()),
+                Err(err) => println!("error: {}", err),
+            }
+        }
+    }
+}
+
+fn print_answer(part: usize, answer: &str) {
+    if answer.contains('\n') {
+        println!("Part {}:\n{}", part, answer);
+    } else {
+        println!("Part {}: {}", part, answer);
+    }
+}
+
+fn bench(args: &Args) -> Result<()> {
+    let mut measurements = vec![];
+    for solution in args.solutions()? {
+        measurements.extend(bench::bench(solution, &input::load(None, solution.input(), args.strict)?, &args.params, args.iterations)?);
+    }
+
+    let baseline = match &args.baseline {
+        Some(path) => {
+            let error = |message: String| Error::Input { path: path.clone(), message };
+            let baseline = fs::read_to_string(path).map_err(|err| error(err.to_string()))?;
+            Some(bench::Baseline::parse(&baseline).map_err(error)?)
+        }
+        None => None,
+    };
+    let regressions = bench::print(&measurements, baseline.as_ref(), args.threshold);
+
+    if let Some(path) = &args.save {
+        fs::write(path, bench::Baseline::serialize(&measurements)).map_err(|err| Error::Input { path: path.clone(), message: err.to_string() })?;
+    }
+    if regressions > 0 {
+        println!("{} phase(s) regressed by more than {}%", regressions, args.threshold);
+        process::exit(1);
+    }
+    Ok(())
+}
+
+fn generate(args: &Args) -> Result<()> {
+    let solution = args.solution("Generate")?;
+    let seed = args.seed.unwrap_or_else(|| {
+        let seed = Random::seed_from_time();
+        log::info(format_args!("seed: {}", seed));
+        seed
+    });
+    print!("{}", solution.generate(&mut Random::new(seed), args.size));
+    Ok(())
+}
+
+fn animate(args: &Args) -> Result<()> {
+    let solution = args.solution("Animate")?;
+    let mut visualizer = solution
+        .visualize(&input::load(args.input.as_deref(), solution.input(), args.strict)?, &args.params)?
+        .ok_or_else(|| Error::usage(&format!("Day {} isn't a simulation, there is nothing to animate!", solution.day())))?;
+    let mut player = Player { viewport: args.viewport, speed: args.speed, paused: args.paused };
+    player
+        .play(visualizer.as_mut(), &visualize::stdin_controls(), &mut std::io::stdout())
+        .map_err(|err| Error::Input { path: "stdout".to_string(), message: err.to_string() })?;
+    Ok(())
+}
+
+fn print_trace(args: &Args) -> Result<()> {
+    let solution = args.solution("Trace")?;
+    let trace = solution
+        .trace(&input::load(args.input.as_deref(), solution.input(), args.strict)?, &args.params)?
+        .ok_or_else(|| Error::usage(&format!("Day {} isn't a simulation, there is nothing to trace!", solution.day())))?;
+    println!("# day {}: {}", solution.day(), solution.name());
+    print!("{}", trace);
+    Ok(())
+}
+
+fn trace_diff(args: &Args) -> Result<()> {
+    let [left, right] = &args.positional[..] else {
+        return Err(Error::usage("Trace-diff needs exactly two trace files!"));
+    };
+    let (left, right) = (Trace::load(Path::new(left))?, Trace::load(Path::new(right))?);
+    match trace::diff(&left, &right) {
+        Some(divergence) => {
+            println!("{}", divergence);
+            process::exit(1);
+        }
+        None => println!("traces are identical, {} steps", left.states.len().saturating_sub(1)),
     }
+    Ok(())
 }
@@ -0,0 +1,110 @@
+use std::str::FromStr;
+
+/// Puzzle constants of a day, which can be overridden without recompiling
+///
+/// The defaults are the values from the puzzle text, days without any constants use `()`.
+pub trait Params: Default {
+    /// Override the parameter named `key`, the message says why the key or the value is wrong
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String>;
+}
+
+impl Params for () {
+    fn set(&mut self, key: &str, _value: &str) -> Result<(), String> {
+        Err(format!("unknown parameter '{}', this day doesn't have any", key))
+    }
+}
+
+/// Value of a parameter parsed as the type of the field it's stored in
+pub fn value<T: FromStr>(key: &str, value: &str) -> Result<T, String> {
+    value.trim().parse().map_err(|_| format!("parameter '{}' can't be '{}'", key, value))
+}
+
+/// Comma separated list of values, e.g. `20,60,100`
+pub fn list<T: FromStr>(key: &str, values: &str) -> Result<Vec<T>, String> {
+    values.split(',').map(|item| value(key, item)).collect()
+}
+
+/// Parameter values to override, each prefixed by its day like `day7.disk_size=70000000`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Overrides {
+    values: Vec<(usize, String, String)>,
+}
+
+impl Overrides {
+    /// Add an assignment in the form of `day7.disk_size=70000000`, later ones win
+    pub fn add(&mut self, assignment: &str) -> Result<(), String> {
+        let (key, value) = assignment
+            .split_once('=')
+            .ok_or_else(|| format!("'{}' has to be in the form of 'day7.disk_size=70000000'", assignment))?;
+        let (day, key) = key
+            .trim()
+            .strip_prefix("day")
+            .and_then(|key| key.split_once('.'))
+            .and_then(|(day, key)| Some((day.parse::<usize>().ok()?, key)))
+            .ok_or_else(|| format!("parameter '{}' has to start with its day, e.g. 'day7.'", key.trim()))?;
+        self.values.push((day, key.to_string(), value.trim().to_string()));
+        Ok(())
+    }
+
+    /// Parse a config file with one assignment per line, empty lines and lines starting with `#` are skipped
+    pub fn parse(config: &str) -> Result<Overrides, String> {
+        let mut overrides = Overrides::default();
+        for (index, line) in config.lines().enumerate() {
+            if line.trim().is_empty() || line.trim_start().starts_with('#') {
+                continue;
+            }
+            overrides.add(line).map_err(|message| format!("line {}: {}", index + 1, message))?;
+        }
+        Ok(overrides)
+    }
+
+    /// Add all of the other overrides, they win over the current ones
+    pub fn extend(&mut self, other: Overrides) {
+        self.values.extend(other.values);
+    }
+
+    /// Parameters of the day, the defaults with the day's overrides applied in order
+    pub fn apply<P: Params>(&self, day: usize) -> Result<P, String> {
+        let mut params = P::default();
+        for (_, key, value) in self.values.iter().filter(|(of, _, _)| *of == day) {
+            params.set(key, value)?;
+        }
+        Ok(params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::params::{self, Overrides, Params};
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Rope {
+        knots: usize,
+        cycles: Vec<usize>,
+    }
+
+    impl Params for Rope {
+        fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+            match key {
+                "knots" => self.knots = params::value(key, value)?,
+                "cycles" => self.cycles = params::list(key, value)?,
+                _ => return Err(format!("unknown parameter '{}'", key)),
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn apply_overrides() {
+        let mut overrides = Overrides::parse("# what if\nday9.knots = 5\n\nday10.width=20\n").unwrap();
+        overrides.add("day9.cycles=1,2").unwrap();
+        assert_eq!(overrides.apply::<Rope>(9), Ok(Rope { knots: 5, cycles: vec![1, 2] }));
+        assert_eq!(overrides.apply::<Rope>(10), Err("unknown parameter 'width'".to_string()));
+        assert_eq!(overrides.apply::<()>(1), Ok(()));
+
+        overrides.add("day9.knots=x").unwrap();
+        assert_eq!(overrides.apply::<Rope>(9), Err("parameter 'knots' can't be 'x'".to_string()));
+        assert!(overrides.add("knots=5").is_err());
+        assert_eq!(Overrides::parse("day9.knots\n"), Err("line 1: 'day9.knots' has to be in the form of 'day7.disk_size=70000000'".to_string()));
+    }
+}
@@ -0,0 +1,286 @@
+use std::str::FromStr;
+
+use crate::error::{Error, Result};
+use crate::geometry::Point;
+
+/// Failure of a [Parser], `column` is the 1-based position of the character it stopped at
+#[derive(Debug, PartialEq)]
+pub struct Failure {
+    pub column: usize,
+    pub message: String,
+}
+
+impl Failure {
+    /// Keep the position but replace the message, usually with the format the day expects
+    pub fn describe(self, message: &str) -> Failure {
+        Failure { column: self.column, message: message.to_string() }
+    }
+
+    /// Parse error of the given day on the given line
+    pub fn at(self, day: usize, line: usize, text: &str) -> Error {
+        Error::parse(day, line, text, &self.message).at_column(self.column)
+    }
+}
+
+pub type Parsed<T> = std::result::Result<T, Failure>;
+
+/// Cursor over a piece of input, every combinator consumes what it recognized
+///
+/// A failed combinator leaves the cursor where it failed, so the column of the failure points at
+/// the offending character. Use [Parser::attempt] when a failure should consume nothing.
+pub struct Parser<'a> {
+    input: &'a str,
+    offset: usize,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(input: &'a str) -> Parser<'a> {
+        Parser { input, offset: 0 }
+    }
+
+    /// Not yet consumed part of the input
+    pub fn rest(&self) -> &'a str {
+        &self.input[self.offset..]
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.rest().is_empty()
+    }
+
+    pub fn column(&self) -> usize {
+        self.input[..self.offset].chars().count() + 1
+    }
+
+    pub fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    /// Failure at the current position
+    pub fn fail<T>(&self, message: &str) -> Parsed<T> {
+        Err(Failure { column: self.column(), message: message.to_string() })
+    }
+
+    /// Consume the text if the input continues with it
+    pub fn eat(&mut self, text: &str) -> bool {
+        if self.rest().starts_with(text) {
+            self.offset += text.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn literal(&mut self, text: &str) -> Parsed<()> {
+        if self.eat(text) {
+            Ok(())
+        } else {
+            self.fail(&format!("Expected '{}'", text))
+        }
+    }
+
+    /// First of the options the input continues with
+    pub fn one_of(&mut self, options: &[&'a str]) -> Parsed<&'a str> {
+        match options.iter().find(|option| self.eat(option)) {
+            Some(option) => Ok(option),
+            None => self.fail(&format!("Expected one of '{}'", options.join("', '"))),
+        }
+    }
+
+    /// Consume characters as long as they match the predicate
+    pub fn take_while<P: Fn(char) -> bool>(&mut self, predicate: P) -> &'a str {
+        let rest = self.rest();
+        let length = rest.find(|c: char| !predicate(c)).unwrap_or(rest.len());
+        self.offset += length;
+        &rest[..length]
+    }
+
+    pub fn spaces(&mut self) {
+        self.take_while(char::is_whitespace);
+    }
+
+    /// Integer with an optional sign, the type decides whether negative numbers fit
+    pub fn int<T: FromStr>(&mut self) -> Parsed<T> {
+        let start = self.offset;
+        self.eat("-");
+        if self.take_while(|c| c.is_ascii_digit()).is_empty() {
+            return self.fail("Expected a number");
+        }
+        let number = &self.input[start..self.offset];
+        number.parse().or_else(|_| {
+            self.offset = start;
+            self.fail(&format!("Number {} is out of range", number))
+        })
+    }
+
+    /// Coordinate pair in the form of `498,4`
+    pub fn point(&mut self) -> Parsed<Point> {
+        let (x, y) = self.pair(Parser::int, ",", Parser::int)?;
+        Ok(Point::new(x, y))
+    }
+
+    pub fn pair<A, B, F, G>(&mut self, mut first: F, separator: &str, mut second: G) -> Parsed<(A, B)>
+    where
+        F: FnMut(&mut Parser<'a>) -> Parsed<A>,
+        G: FnMut(&mut Parser<'a>) -> Parsed<B>,
+    {
+        let first = first(self)?;
+        self.literal(separator)?;
+        Ok((first, second(self)?))
+    }
+
+    /// One or more items separated by the separator
+    pub fn separated<T, F>(&mut self, separator: &str, mut item: F) -> Parsed<Vec<T>>
+    where
+        F: FnMut(&mut Parser<'a>) -> Parsed<T>,
+    {
+        let mut items = vec![item(self)?];
+        while self.eat(separator) {
+            items.push(item(self)?);
+        }
+        Ok(items)
+    }
+
+    /// Possibly empty list of items enclosed in brackets, items can be lists themselves
+    pub fn bracketed<T, F>(&mut self, open: &str, separator: &str, close: &str, mut item: F) -> Parsed<Vec<T>>
+    where
+        F: FnMut(&mut Parser<'a>) -> Parsed<T>,
+    {
+        self.literal(open)?;
+        let mut items = vec![];
+        if self.eat(close) {
+            return Ok(items);
+        }
+        loop {
+            items.push(item(self)?);
+            if self.eat(close) {
+                return Ok(items);
+            }
+            if self.is_done() {
+                return self.fail("Unbalanced brackets");
+            }
+            self.literal(separator)
+                .or_else(|_| self.fail(&format!("Expected '{}' or '{}'", separator, close)))?;
+        }
+    }
+
+    /// Run the parser, rewinding the input when it fails
+    pub fn attempt<T, F>(&mut self, parser: F) -> Parsed<T>
+    where
+        F: FnOnce(&mut Parser<'a>) -> Parsed<T>,
+    {
+        let start = self.offset;
+        parser(self).inspect_err(|_| self.offset = start)
+    }
+
+    /// Succeeds only when the whole input was consumed
+    pub fn end(&self) -> Parsed<()> {
+        if self.is_done() {
+            Ok(())
+        } else {
+            self.fail(&format!("Unexpected '{}'", self.rest()))
+        }
+    }
+}
+
+/// Parse the whole line, failures become parse errors of the day on the given line
+pub fn line<'a, T, F>(day: usize, number: usize, text: &'a str, parser: F) -> Result<T>
+where
+    F: FnOnce(&mut Parser<'a>) -> Parsed<T>,
+{
+    let mut cursor = Parser::new(text);
+    parser(&mut cursor)
+        .and_then(|value| cursor.end().map(|_| value))
+        .map_err(|failure| failure.at(day, number, text))
+}
+
+/// Parse every line of the input, `first_line` is the line number of the first of them
+pub fn lines<'a, T, F>(day: usize, first_line: usize, input: &'a str, mut parser: F) -> Result<Vec<T>>
+where
+    F: FnMut(&mut Parser<'a>) -> Parsed<T>,
+{
+    input
+        .lines()
+        .enumerate()
+        .map(|(index, text)| line(day, first_line + index, text, &mut parser))
+        .collect()
+}
+
+/// Blocks of lines separated by blank lines, each with the line number of its first line
+pub fn blocks(input: &str) -> Vec<(usize, &str)> {
+    let mut blocks = vec![];
+    let mut start = None;
+    let mut offset = 0;
+    for (index, text) in input.split_inclusive('\n').enumerate() {
+        if text.trim().is_empty() {
+            if let Some((line, begin)) = start.take() {
+                blocks.push((line, input[begin..offset].trim_end_matches('\n')));
+            }
+        } else if start.is_none() {
+            start = Some((index + 1, offset));
+        }
+        offset += text.len();
+    }
+    if let Some((line, begin)) = start {
+        blocks.push((line, input[begin..].trim_end_matches('\n')));
+    }
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error;
+    use crate::geometry::Point;
+    use crate::parse::{self, Failure, Parsed, Parser};
+
+    #[test]
+    fn numbers_and_lists() {
+        let mut parser = Parser::new("-12,7 -> 3,4");
+        assert_eq!(parser.separated(" -> ", Parser::point), Ok(vec![Point::new(-12, 7), Point::new(3, 4)]));
+        assert!(parser.is_done());
+
+        assert_eq!(Parser::new("x").int::<u32>(), Err(Failure { column: 1, message: "Expected a number".to_string() }));
+        assert_eq!(Parser::new("-1").int::<u32>(), Err(Failure { column: 1, message: "Number -1 is out of range".to_string() }));
+        assert_eq!(Parser::new("2-4").pair(Parser::int::<u8>, "-", Parser::int), Ok((2, 4)));
+
+        let mut parser = Parser::new("1, 2;");
+        assert_eq!(parser.separated(", ", Parser::int::<u8>), Ok(vec![1, 2]));
+        assert_eq!(parser.end(), Err(Failure { column: 5, message: "Unexpected ';'".to_string() }));
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Nested {
+        Number(u8),
+        List(Vec<Nested>),
+    }
+
+    fn nested(parser: &mut Parser) -> Parsed<Nested> {
+        if parser.peek() == Some('[') {
+            parser.bracketed("[", ",", "]", nested).map(Nested::List)
+        } else {
+            parser.int().map(Nested::Number)
+        }
+    }
+
+    #[test]
+    fn bracket_nesting() {
+        use Nested::{List, Number};
+        assert_eq!(Parser::new("[1,[],[2,[3]]]").attempt(nested), Ok(List(vec![Number(1), List(vec![]), List(vec![Number(2), List(vec![Number(3)])])])));
+        assert_eq!(Parser::new("[1,[2]").attempt(nested).unwrap_err().message, "Unbalanced brackets");
+        assert_eq!(Parser::new("[1;2]").attempt(nested).unwrap_err(), Failure { column: 3, message: "Expected ',' or ']'".to_string() });
+
+        let mut parser = Parser::new("[x]");
+        assert!(parser.attempt(nested).is_err());
+        assert_eq!(parser.rest(), "[x]");
+    }
+
+    #[test]
+    fn lines_and_blocks() {
+        assert_eq!(parse::lines(0, 3, "1\n2\n", Parser::int::<u8>), Ok(vec![1, 2]));
+        assert_eq!(
+            parse::lines(0, 3, "1\n2x\n", Parser::int::<u8>),
+            Err(Error::parse(0, 4, "2x", "Unexpected 'x'").at_column(2))
+        );
+        assert_eq!(parse::blocks("a\nb\n\n\nc\n"), vec![(1, "a\nb"), (5, "c")]);
+        assert_eq!(parse::blocks("\n  x\n \n"), vec![(2, "  x")]);
+    }
+}
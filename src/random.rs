@@ -0,0 +1,81 @@
+use std::ops::Range;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Small deterministic pseudo-random generator (SplitMix64) for generating puzzle inputs
+///
+/// Not suitable for anything where the quality of randomness matters, but the same seed always
+/// produces the same sequence, which is what reproducible inputs need.
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Random {
+        Random { state: seed }
+    }
+
+    /// Seed taken from the current time, for when reproducibility isn't needed
+    pub fn seed_from_time() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_nanos() as u64)
+            .unwrap_or(0)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Random number from the range, which mustn't be empty
+    pub fn range(&mut self, range: Range<usize>) -> usize {
+        assert!(!range.is_empty(), "Can't pick a random number from an empty range");
+        range.start + (self.next_u64() % (range.end - range.start) as u64) as usize
+    }
+
+    /// True with the probability of `numerator / denominator`
+    pub fn chance(&mut self, numerator: usize, denominator: usize) -> bool {
+        self.range(0..denominator) < numerator
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.range(0..items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for index in (1..items.len()).rev() {
+            items.swap(index, self.range(0..index + 1));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::random::Random;
+
+    #[test]
+    fn same_seed_same_sequence() {
+        let mut first = Random::new(42);
+        let mut second = Random::new(42);
+        for _ in 0..100 {
+            assert_eq!(first.next_u64(), second.next_u64());
+        }
+        assert_ne!(Random::new(1).next_u64(), Random::new(2).next_u64());
+    }
+
+    #[test]
+    fn range_bounds() {
+        let mut random = Random::new(7);
+        for _ in 0..1000 {
+            let number = random.range(5..8);
+            assert!((5..8).contains(&number));
+        }
+        let mut items = (0..10).collect::<Vec<usize>>();
+        random.shuffle(&mut items);
+        items.sort();
+        assert_eq!(items, (0..10).collect::<Vec<usize>>());
+    }
+}
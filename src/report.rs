@@ -0,0 +1,274 @@
+use std::time::Duration;
+
+use crate::batch::Batch;
+use crate::error::Result;
+use crate::explain::Note;
+use crate::isolate::Outcome;
+use crate::solution::{Part, Run, Runnable};
+
+/// Output format of the answers
+#[derive(Copy, Clone, PartialEq)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+impl Format {
+    pub fn parse(format: &str) -> Option<Format> {
+        match format {
+            "text" => Some(Format::Text),
+            "json" => Some(Format::Json),
+            _ => None,
+        }
+    }
+}
+
+const HEADER: [&str; 5] = ["Day", "Part", "Answer", "Parse", "Solve"];
+
+/// Print answers and timings of all runs as a table, one row per day and part
+///
+/// Multi-line answers (e.g. the CRT screen) continue on following rows in the answer column.
+/// Solutions which failed, timed out or panicked get rows saying so.
+pub fn print_table(runs: &[(&dyn Runnable, Result<Outcome>)]) {
+    let rows = runs
+        .iter()
+        .flat_map(|(solution, outcome)| result_rows(&solution.day().to_string(), outcome))
+        .collect::<Vec<[String; 5]>>();
+    print_rows(HEADER, &rows);
+
+    let total = runs.iter().filter_map(|(_, outcome)| solved(outcome.as_ref().ok()?)).map(total_time).sum::<Duration>();
+    println!("Total time: {}", format_duration(total));
+    print_explanations(runs.iter().filter_map(|(solution, outcome)| Some((format!("Day {}", solution.day()), solved(outcome.as_ref().ok()?)?))));
+}
+
+/// Print answers and timings of a day on every input of a batch, inputs which failed show the error
+pub fn print_batch(batches: &[Batch]) {
+    let rows = batches.iter().flat_map(|batch| result_rows(&batch.file, &batch.run)).collect::<Vec<[String; 5]>>();
+    print_rows(["File", "Part", "Answer", "Parse", "Solve"], &rows);
+
+    let total = batches.iter().filter_map(|batch| solved(batch.run.as_ref().ok()?)).map(total_time).sum::<Duration>();
+    let failed = batches.iter().filter(|batch| !batch.run.as_ref().is_ok_and(|outcome| solved(outcome).is_some())).count();
+    println!("{} input(s), {} failed, total time: {}", batches.len(), failed, format_duration(total));
+    print_explanations(batches.iter().filter_map(|batch| Some((batch.file.clone(), solved(batch.run.as_ref().ok()?)?))));
+}
+
+/// Notes justifying the answer of a part, one per line, indented so they stand out from the answers
+pub fn print_explanation(explanation: &[Note]) {
+    for note in explanation {
+        println!("  {}", note);
+    }
+}
+
+/// Explanations of the parts which have one, headed by the label of their run and the part
+fn print_explanations<'a, I: Iterator<Item = (String, &'a Run)>>(runs: I) {
+    for (label, run) in runs {
+        for (index, part) in run.parts.iter().enumerate().filter(|(_, part)| !part.explanation.is_empty()) {
+            println!("\n{} part {}:", label, index + 1);
+            print_explanation(&part.explanation);
+        }
+    }
+}
+
+fn solved(outcome: &Outcome) -> Option<&Run> {
+    match outcome {
+        Outcome::Solved(run) => Some(run),
+        _ => None,
+    }
+}
+
+/// Rows of a solved run, or of the reason why it wasn't solved
+fn result_rows(label: &str, outcome: &Result<Outcome>) -> Vec<[String; 5]> {
+    match outcome {
+        Ok(Outcome::Solved(run)) => run_rows(label, run),
+        Ok(failed) => failure_rows(label, &failed.failure().unwrap()),
+        Err(err) => failure_rows(label, &format!("error: {}", err)),
+    }
+}
+
+/// Message spanning the answer column of as many rows as it has lines
+fn failure_rows(label: &str, message: &str) -> Vec<[String; 5]> {
+    message
+        .lines()
+        .enumerate()
+        .map(|(index, line)| {
+            let label = if index == 0 { label.to_string() } else { String::new() };
+            [label, String::new(), line.to_string(), String::new(), String::new()]
+        })
+        .collect()
+}
+
+/// Rows of both parts of a run, labeled by the day or the input in the first column
+fn run_rows(label: &str, run: &Run) -> Vec<[String; 5]> {
+    let mut rows = vec![];
+    for (index, part) in run.parts.iter().enumerate() {
+        for (line_index, line) in part.answer.lines().enumerate() {
+            rows.push(if line_index == 0 {
+                [
+                    label.to_string(),
+                    (index + 1).to_string(),
+                    line.to_string(),
+                    if index == 0 { format_duration(run.parse_time) } else { String::new() },
+                    format_duration(part.time),
+                ]
+            } else {
+                [String::new(), String::new(), line.to_string(), String::new(), String::new()]
+            });
+        }
+    }
+    rows
+}
+
+fn total_time(run: &Run) -> Duration {
+    run.parse_time + run.parts.iter().map(|part| part.time).sum::<Duration>()
+}
+
+fn print_rows(header: [&str; 5], rows: &[[String; 5]]) {
+    let mut widths = header.map(|title| title.len());
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    print_row(&header.map(|title| title.to_string()), &widths);
+    println!("{}", widths.iter().map(|width| "-".repeat(*width)).collect::<Vec<String>>().join("-+-"));
+    rows.iter().for_each(|row| print_row(row, &widths));
+}
+
+fn print_row(row: &[String; 5], widths: &[usize; 5]) {
+    let cells = row
+        .iter()
+        .zip(widths)
+        .enumerate()
+        .map(|(index, (cell, width))| if index >= 3 { format!("{:>width$}", cell) } else { format!("{:<width$}", cell) })
+        .collect::<Vec<String>>();
+    println!("{}", cells.join(" | ").trim_end());
+}
+
+/// Print one JSON object per line for every day and part
+///
+/// Keys are stable: `day`, `part`, `answer` and `duration_ns`, the latter being the time spent
+/// solving the part (parsing excluded). Parts explained with `--explain` add an `explanation` list of
+/// `{"key": ..., "value": ...}` objects. Solutions which failed, timed out or panicked have a single
+/// `{"day": ..., "error": ...}` object instead.
+pub fn print_json(runs: &[(&dyn Runnable, Result<Outcome>)]) {
+    for (solution, outcome) in runs {
+        match outcome {
+            Ok(Outcome::Solved(run)) => {
+                for (index, part) in run.parts.iter().enumerate() {
+                    println!("{}", json_part(solution.day(), index + 1, part));
+                }
+            }
+            Ok(failed) => println!("{{\"day\":{},\"error\":{}}}", solution.day(), json_string(&failed.failure().unwrap())),
+            Err(err) => println!("{{\"day\":{},\"error\":{}}}", solution.day(), json_string(&err.to_string())),
+        }
+    }
+}
+
+/// Print the JSON objects of [print_json] for every input of a batch, with the `file` key added
+///
+/// Inputs which failed have a single `{"file": ..., "error": ...}` object instead.
+pub fn print_batch_json(day: usize, batches: &[Batch]) {
+    for batch in batches {
+        let file = json_string(&batch.file);
+        match &batch.run {
+            Ok(Outcome::Solved(run)) => {
+                for (index, part) in run.parts.iter().enumerate() {
+                    println!("{{\"file\":{},{}", file, &json_part(day, index + 1, part)[1..]);
+                }
+            }
+            Ok(failed) => println!("{{\"file\":{},\"error\":{}}}", file, json_string(&failed.failure().unwrap())),
+            Err(err) => println!("{{\"file\":{},\"error\":{}}}", file, json_string(&err.to_string())),
+        }
+    }
+}
+
+/// JSON object of the part, with its explanation when it has one
+fn json_part(day: usize, index: usize, part: &Part) -> String {
+    let object = json_object(day, index, &part.answer, part.time);
+    if part.explanation.is_empty() {
+        return object;
+    }
+    let notes = part
+        .explanation
+        .iter()
+        .map(|note| format!("{{\"key\":{},\"value\":{}}}", json_string(&note.key), json_string(&note.value)))
+        .collect::<Vec<String>>();
+    format!("{},\"explanation\":[{}]}}", &object[..object.len() - 1], notes.join(","))
+}
+
+fn json_object(day: usize, part: usize, answer: &str, duration: Duration) -> String {
+    format!(
+        "{{\"day\":{},\"part\":{},\"answer\":{},\"duration_ns\":{}}}",
+        day,
+        part,
+        json_string(answer),
+        duration.as_nanos()
+    )
+}
+
+fn json_string(value: &str) -> String {
+    let mut result = String::from("\"");
+    for character in value.chars() {
+        match character {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if c.is_control() => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+/// Format duration with three decimal places in the most fitting unit
+pub fn format_duration(duration: Duration) -> String {
+    let nanos = duration.as_nanos() as f64;
+    if nanos >= 1e9 {
+        format!("{:.3}s", nanos / 1e9)
+    } else if nanos >= 1e6 {
+        format!("{:.3}ms", nanos / 1e6)
+    } else if nanos >= 1e3 {
+        format!("{:.3}µs", nanos / 1e3)
+    } else {
+        format!("{}ns", nanos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use crate::explain::Note;
+    use crate::report::{format_duration, json_object, json_part};
+    use crate::solution::Part;
+
+    #[test]
+    fn duration_units() {
+        assert_eq!(format_duration(Duration::from_nanos(999)), "999ns");
+        assert_eq!(format_duration(Duration::from_nanos(1_500)), "1.500µs");
+        assert_eq!(format_duration(Duration::from_micros(2_250)), "2.250ms");
+        assert_eq!(format_duration(Duration::from_millis(10_001)), "10.001s");
+    }
+
+    #[test]
+    fn json_escaping() {
+        assert_eq!(
+            json_object(10, 2, "a\"\nb", Duration::from_nanos(42)),
+            r#"{"day":10,"part":2,"answer":"a\"\nb","duration_ns":42}"#
+        );
+    }
+
+    #[test]
+    fn json_explanation() {
+        let mut part = Part { answer: "13".to_string(), time: Duration::from_nanos(7), explanation: vec![] };
+        assert_eq!(json_part(13, 1, &part), r#"{"day":13,"part":1,"answer":"13","duration_ns":7}"#);
+        part.explanation.push(Note { key: "pairs in order".to_string(), value: "1, \"2\"".to_string() });
+        assert_eq!(
+            json_part(13, 1, &part),
+            r#"{"day":13,"part":1,"answer":"13","duration_ns":7,"explanation":[{"key":"pairs in order","value":"1, \"2\""}]}"#
+        );
+    }
+}
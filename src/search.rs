@@ -0,0 +1,206 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+
+/// Outcome of a graph search: distances of all reached nodes and how they were reached
+///
+/// With an early exit only nodes explored before reaching the goal are known.
+pub struct Search<N> {
+    distances: HashMap<N, usize>,
+    previous: HashMap<N, N>,
+    goal: Option<N>,
+}
+
+impl<N: Copy + Eq + Hash> Search<N> {
+    fn new() -> Search<N> {
+        Search { distances: HashMap::new(), previous: HashMap::new(), goal: None }
+    }
+
+    /// First node satisfying the goal, `None` when none could be reached
+    pub fn goal(&self) -> Option<N> {
+        self.goal
+    }
+
+    /// Distance of the goal from the closest start
+    pub fn goal_distance(&self) -> Option<usize> {
+        self.distance(self.goal?)
+    }
+
+    pub fn distance(&self, node: N) -> Option<usize> {
+        self.distances.get(&node).copied()
+    }
+
+    pub fn distances(&self) -> &HashMap<N, usize> {
+        &self.distances
+    }
+
+    /// Nodes from a start up to the given node, both included
+    pub fn path(&self, node: N) -> Option<Vec<N>> {
+        self.distances.get(&node)?;
+        let mut path = vec![node];
+        while let Some(previous) = self.previous.get(path.last().unwrap()) {
+            path.push(*previous);
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Record the node unless it was already reached by a path at most as long
+    fn relax(&mut self, node: N, from: Option<N>, distance: usize) -> bool {
+        if self.distances.get(&node).is_some_and(|known| *known <= distance) {
+            return false;
+        }
+        self.distances.insert(node, distance);
+        if let Some(from) = from {
+            self.previous.insert(node, from);
+        }
+        true
+    }
+}
+
+/// Breadth-first search from all starts at once, every edge has the length of 1
+///
+/// Stops as soon as a node satisfying `goal` is dequeued, use `|_| false` to explore everything.
+pub fn bfs<N, S, F, I, G>(starts: S, mut neighbors: F, mut goal: G) -> Search<N>
+where
+    N: Copy + Eq + Hash,
+    S: IntoIterator<Item = N>,
+    F: FnMut(N) -> I,
+    I: IntoIterator<Item = N>,
+    G: FnMut(N) -> bool,
+{
+    let mut search = Search::new();
+    let mut queue = VecDeque::new();
+    for start in starts {
+        if search.relax(start, None, 0) {
+            queue.push_back(start);
+        }
+    }
+
+    while let Some(node) = queue.pop_front() {
+        if goal(node) {
+            search.goal = Some(node);
+            break;
+        }
+        let distance = search.distances[&node] + 1;
+        for neighbor in neighbors(node) {
+            if !search.distances.contains_key(&neighbor) {
+                search.relax(neighbor, Some(node), distance);
+                queue.push_back(neighbor);
+            }
+        }
+    }
+    search
+}
+
+/// Dijkstra's shortest paths from all starts at once, `neighbors` returns nodes with edge lengths
+pub fn dijkstra<N, S, F, I, G>(starts: S, neighbors: F, goal: G) -> Search<N>
+where
+    N: Copy + Eq + Hash,
+    S: IntoIterator<Item = N>,
+    F: FnMut(N) -> I,
+    I: IntoIterator<Item = (N, usize)>,
+    G: FnMut(N) -> bool,
+{
+    astar(starts, neighbors, goal, |_| 0)
+}
+
+/// A* search guided by `heuristic`, which mustn't overestimate the remaining distance to the goal
+pub fn astar<N, S, F, I, G, H>(starts: S, mut neighbors: F, mut goal: G, mut heuristic: H) -> Search<N>
+where
+    N: Copy + Eq + Hash,
+    S: IntoIterator<Item = N>,
+    F: FnMut(N) -> I,
+    I: IntoIterator<Item = (N, usize)>,
+    G: FnMut(N) -> bool,
+    H: FnMut(N) -> usize,
+{
+    let mut search = Search::new();
+    // nodes are kept aside so they don't need to be ordered, the heap only refers to them
+    let mut nodes = vec![];
+    let mut heap = BinaryHeap::new();
+    for start in starts {
+        if search.relax(start, None, 0) {
+            heap.push(Reverse((heuristic(start), 0, nodes.len())));
+            nodes.push(start);
+        }
+    }
+
+    while let Some(Reverse((_, distance, index))) = heap.pop() {
+        let node = nodes[index];
+        if search.distances[&node] < distance {
+            continue;
+        }
+        if goal(node) {
+            search.goal = Some(node);
+            break;
+        }
+        for (neighbor, length) in neighbors(node) {
+            let distance = distance + length;
+            if search.relax(neighbor, Some(node), distance) {
+                heap.push(Reverse((distance + heuristic(neighbor), distance, nodes.len())));
+                nodes.push(neighbor);
+            }
+        }
+    }
+    search
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geometry::Point;
+    use crate::grid::Grid;
+    use crate::search::{astar, bfs, dijkstra};
+
+    fn maze() -> Grid<char> {
+        Grid::parse(0, "S..#....\n.#.#.##.\n.#...#E.\n.####.#.\n........", "maze", Ok).unwrap()
+    }
+
+    #[test]
+    fn bfs_shortest_path() {
+        let maze = maze();
+        let start = maze.find(|cell| *cell == 'S').unwrap();
+        let open = |point: Point| maze.neighbors_4(point).filter(|neighbor| maze.get(*neighbor) != Some(&'#')).collect::<Vec<Point>>();
+        let search = bfs([start], open, |point| maze.get(point) == Some(&'E'));
+
+        assert_eq!(search.goal(), Some(Point::new(6, 2)));
+        assert_eq!(search.goal_distance(), Some(14));
+        let path = search.path(search.goal().unwrap()).unwrap();
+        assert_eq!(path.len(), 15);
+        assert_eq!(path[0], start);
+        assert!(path.windows(2).all(|step| step[0].manhattan(step[1]) == 1));
+
+        let everything = bfs([start], open, |_| false);
+        assert_eq!(everything.goal(), None);
+        assert_eq!(everything.distances().len(), maze.iter().filter(|(_, cell)| **cell != '#').count());
+    }
+
+    #[test]
+    fn multiple_starts() {
+        let line = |node: usize| [node.saturating_sub(1), (node + 1).min(10)];
+        let search = bfs([0, 10], line, |_| false);
+        assert_eq!(search.distance(4), Some(4));
+        assert_eq!(search.distance(7), Some(3));
+        assert_eq!(search.path(7).unwrap(), vec![10, 9, 8, 7]);
+    }
+
+    #[test]
+    fn weighted_search() {
+        // the direct edge is longer than the detour
+        let edges = |node: char| match node {
+            'a' => vec![('b', 7), ('c', 2)],
+            'c' => vec![('d', 2)],
+            'd' => vec![('b', 1)],
+            _ => vec![],
+        };
+        let search = dijkstra(['a'], edges, |node| node == 'b');
+        assert_eq!(search.goal_distance(), Some(5));
+        assert_eq!(search.path('b').unwrap(), vec!['a', 'c', 'd', 'b']);
+
+        let maze = maze();
+        let (start, end) = (maze.find(|cell| *cell == 'S').unwrap(), maze.find(|cell| *cell == 'E').unwrap());
+        let open = |point: Point| maze.neighbors_4(point).filter(|neighbor| maze.get(*neighbor) != Some(&'#')).map(|neighbor| (neighbor, 1)).collect::<Vec<(Point, usize)>>();
+        let search = astar([start], open, |point| point == end, |point| point.manhattan(end));
+        assert_eq!(search.goal_distance(), Some(14));
+    }
+}
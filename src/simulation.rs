@@ -0,0 +1,177 @@
+use std::collections::VecDeque;
+
+use crate::error::Result;
+use crate::visualize::{Frame, Visualizer};
+
+/// Discrete-step simulation which can be rewound to an earlier state
+pub trait Simulation {
+    /// Everything needed to continue the simulation from the moment the snapshot was taken
+    type State: Clone;
+
+    /// Advance by a single step, `false` when the simulation is over and nothing happened
+    fn step(&mut self) -> Result<bool>;
+
+    fn snapshot(&self) -> Self::State;
+
+    fn restore(&mut self, state: Self::State);
+
+    /// Step until the predicate holds (it's checked before every step) or the simulation is over
+    ///
+    /// Returns the number of steps taken.
+    fn run_until<P: FnMut(&Self) -> bool>(&mut self, mut predicate: P) -> Result<usize>
+    where
+        Self: Sized,
+    {
+        let mut steps = 0;
+        while !predicate(self) && self.step()? {
+            steps += 1;
+        }
+        Ok(steps)
+    }
+}
+
+/// Simulation which can draw its current state
+pub trait Drawable {
+    fn frame(&self) -> Frame;
+}
+
+/// Simulation which can describe its current state on a single line, see [Trace](crate::trace::Trace)
+pub trait Traceable {
+    /// Equal states have to be described by equal lines, so traces of two runs can be compared
+    fn trace(&self) -> String;
+}
+
+/// Runs a simulation while recording its states, so it can be stepped back and forth
+///
+/// Only the last `limit` states are kept, stepping back stops at the oldest of them. Stepping
+/// forward replays recorded states before simulating new ones.
+pub struct Driver<S: Simulation> {
+    simulation: S,
+    history: VecDeque<S::State>,
+    /// Index of the current state in the history
+    position: usize,
+    limit: usize,
+    steps: usize,
+}
+
+impl<S: Simulation> Driver<S> {
+    pub const DEFAULT_LIMIT: usize = 256;
+
+    pub fn new(simulation: S) -> Driver<S> {
+        Driver::with_limit(simulation, Driver::<S>::DEFAULT_LIMIT)
+    }
+
+    pub fn with_limit(simulation: S, limit: usize) -> Driver<S> {
+        let history = VecDeque::from([simulation.snapshot()]);
+        Driver { simulation, history, position: 0, limit: limit.max(1), steps: 0 }
+    }
+
+    pub fn simulation(&self) -> &S {
+        &self.simulation
+    }
+
+    /// Number of steps from the start of the simulation to the current state
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// Move one step forward, `false` when the simulation is over
+    pub fn forward(&mut self) -> Result<bool> {
+        if let Some(state) = self.history.get(self.position + 1) {
+            self.simulation.restore(state.clone());
+            self.position += 1;
+        } else {
+            if !self.simulation.step()? {
+                return Ok(false);
+            }
+            self.history.push_back(self.simulation.snapshot());
+            if self.history.len() > self.limit {
+                self.history.pop_front();
+            }
+            self.position = self.history.len() - 1;
+        }
+        self.steps += 1;
+        Ok(true)
+    }
+
+    /// Move one step back, `false` when there is no earlier state recorded
+    pub fn back(&mut self) -> bool {
+        if self.position == 0 {
+            return false;
+        }
+        self.position -= 1;
+        self.simulation.restore(self.history[self.position].clone());
+        self.steps -= 1;
+        true
+    }
+}
+
+impl<S: Simulation + Drawable> Visualizer for Driver<S> {
+    /// Failed steps end the animation, the answers report such errors properly
+    fn step(&mut self) -> bool {
+        self.forward().unwrap_or(false)
+    }
+
+    fn back(&mut self) -> bool {
+        Driver::back(self)
+    }
+
+    fn frame(&self) -> Frame {
+        self.simulation.frame()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Result;
+    use crate::simulation::{Driver, Simulation};
+
+    /// Counter which ends at 10
+    struct Counter(usize);
+
+    impl Simulation for Counter {
+        type State = usize;
+
+        fn step(&mut self) -> Result<bool> {
+            if self.0 == 10 {
+                return Ok(false);
+            }
+            self.0 += 1;
+            Ok(true)
+        }
+
+        fn snapshot(&self) -> usize {
+            self.0
+        }
+
+        fn restore(&mut self, state: usize) {
+            self.0 = state;
+        }
+    }
+
+    #[test]
+    fn run_until() {
+        let mut counter = Counter(0);
+        assert_eq!(counter.run_until(|counter| counter.0 == 4), Ok(4));
+        assert_eq!(counter.run_until(|_| false), Ok(6));
+        assert_eq!(counter.0, 10);
+    }
+
+    #[test]
+    fn history() {
+        let mut driver = Driver::with_limit(Counter(0), 3);
+        for _ in 0..5 {
+            assert_eq!(driver.forward(), Ok(true));
+        }
+        assert!(driver.back());
+        assert!(driver.back());
+        assert!(!driver.back());
+        assert_eq!((driver.simulation().0, driver.steps()), (3, 3));
+
+        for _ in 0..7 {
+            driver.forward().unwrap();
+        }
+        assert_eq!(driver.forward(), Ok(false));
+        assert_eq!((driver.simulation().0, driver.steps()), (10, 10));
+    }
+}
@@ -0,0 +1,216 @@
+use std::time::{Duration, Instant};
+
+use crate::error::{Error, Result};
+use crate::explain::{self, Note};
+use crate::image::Picture;
+use crate::input;
+use crate::params::{Overrides, Params};
+use crate::random::Random;
+use crate::trace::Trace;
+use crate::visualize::Visualizer;
+use crate::{day_1, day_10, day_11, day_12, day_13, day_14, day_2, day_3, day_4, day_5, day_6, day_7, day_8, day_9};
+
+/// Solution of a single day's puzzle
+///
+/// Input is parsed once and then shared by both parts. Answers are returned as strings as some
+/// days (e.g. crane rearrangement) don't have a numeric answer. Malformed input is reported by
+/// `parse`, parts fail only when the input has no answer. Constants from the puzzle text are
+/// [Params], so the parts can be run with different ones.
+pub trait Solution {
+    type Input;
+
+    type Params: Params;
+
+    fn name(&self) -> &'static str;
+
+    fn day(&self) -> usize;
+
+    /// Name of the puzzle input file in the resources directory
+    fn input(&self) -> &'static str;
+
+    fn parse(&self, input: &str) -> Result<Self::Input>;
+
+    fn part1(&self, input: &Self::Input, params: &Self::Params) -> Result<String>;
+
+    fn part2(&self, input: &Self::Input, params: &Self::Params) -> Result<String>;
+
+    /// Random valid puzzle input, `size` is the number of its main elements (elves, moves, rows...)
+    fn generate(&self, random: &mut Random, size: usize) -> String;
+
+    /// Step by step animation of the puzzle, only simulations have one
+    fn visualize(&self, _input: &Self::Input, _params: &Self::Params) -> Option<Box<dyn Visualizer>> {
+        None
+    }
+
+    /// Picture of the solved puzzle for sharing, only days with something to look at have one
+    fn export(&self, _input: &Self::Input, _params: &Self::Params) -> Option<Picture> {
+        None
+    }
+
+    /// States of the puzzle's simulation step by step, only simulations have one
+    fn trace(&self, _input: &Self::Input, _params: &Self::Params) -> Result<Option<Trace>> {
+        Ok(None)
+    }
+}
+
+/// Object safe counterpart of [Solution], so days with different inputs can share one registry
+pub trait Runnable: Sync {
+    fn name(&self) -> &'static str;
+
+    fn day(&self) -> usize;
+
+    fn input(&self) -> &'static str;
+
+    /// Solve both parts, the input is [normalized](input::normalize) first and the day's overrides
+    /// are applied to its [Params]
+    fn run(&self, input: &str, overrides: &Overrides) -> Result<Run>;
+
+    fn generate(&self, random: &mut Random, size: usize) -> String;
+
+    /// Animation of the normalized and parsed input, `None` for days which aren't simulations
+    fn visualize(&self, input: &str, overrides: &Overrides) -> Result<Option<Box<dyn Visualizer>>>;
+
+    /// Picture of the normalized and parsed input, `None` for days which don't have one
+    fn export(&self, input: &str, overrides: &Overrides) -> Result<Option<Picture>>;
+
+    /// Trace of the normalized and parsed input, `None` for days which aren't simulations
+    fn trace(&self, input: &str, overrides: &Overrides) -> Result<Option<Trace>>;
+}
+
+/// Answers of both parts together with wall-clock time spent in each phase
+pub struct Run {
+    pub parse_time: Duration,
+    pub parts: [Part; 2],
+}
+
+pub struct Part {
+    pub answer: String,
+    pub time: Duration,
+    /// Notes justifying the answer, only collected when [explanations](explain) are enabled
+    pub explanation: Vec<Note>,
+}
+
+impl Part {
+    fn measure<F: FnOnce() -> Result<String>>(solve: F) -> Result<Part> {
+        let start = Instant::now();
+        let (answer, explanation) = explain::collect(solve);
+        let time = start.elapsed();
+        Ok(Part { answer: answer?, time, explanation })
+    }
+}
+
+impl<S: Solution + Sync> Runnable for S {
+    fn name(&self) -> &'static str {
+        Solution::name(self)
+    }
+
+    fn day(&self) -> usize {
+        Solution::day(self)
+    }
+
+    fn input(&self) -> &'static str {
+        Solution::input(self)
+    }
+
+    fn run(&self, input: &str, overrides: &Overrides) -> Result<Run> {
+        let params = params(self, overrides)?;
+        let (input, _) = input::normalize(input);
+        let start = Instant::now();
+        let parsed = self.parse(&input)?;
+        let parse_time = start.elapsed();
+        Ok(Run {
+            parse_time,
+            parts: [Part::measure(|| self.part1(&parsed, &params))?, Part::measure(|| self.part2(&parsed, &params))?],
+        })
+    }
+
+    fn generate(&self, random: &mut Random, size: usize) -> String {
+        Solution::generate(self, random, size)
+    }
+
+    fn visualize(&self, input: &str, overrides: &Overrides) -> Result<Option<Box<dyn Visualizer>>> {
+        let params = params(self, overrides)?;
+        let (input, _) = input::normalize(input);
+        Ok(Solution::visualize(self, &self.parse(&input)?, &params))
+    }
+
+    fn export(&self, input: &str, overrides: &Overrides) -> Result<Option<Picture>> {
+        let params = params(self, overrides)?;
+        let (input, _) = input::normalize(input);
+        Ok(Solution::export(self, &self.parse(&input)?, &params))
+    }
+
+    fn trace(&self, input: &str, overrides: &Overrides) -> Result<Option<Trace>> {
+        let params = params(self, overrides)?;
+        let (input, _) = input::normalize(input);
+        Solution::trace(self, &self.parse(&input)?, &params)
+    }
+}
+
+fn params<S: Solution>(solution: &S, overrides: &Overrides) -> Result<S::Params> {
+    let day = solution.day();
+    overrides.apply(day).map_err(|message| Error::Param { day, message })
+}
+
+/// All solved days, ordered by day number
+pub static SOLUTIONS: [&dyn Runnable; 14] = [
+    &day_1::Day1,
+    &day_2::Day2,
+    &day_3::Day3,
+    &day_4::Day4,
+    &day_5::Day5,
+    &day_6::Day6,
+    &day_7::Day7,
+    &day_8::Day8,
+    &day_9::Day9,
+    &day_10::Day10,
+    &day_11::Day11,
+    &day_12::Day12,
+    &day_13::Day13,
+    &day_14::Day14,
+];
+
+pub fn find(day: usize) -> Option<&'static dyn Runnable> {
+    SOLUTIONS.iter().find(|solution| solution.day() == day).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::params::Overrides;
+    use crate::random::Random;
+    use crate::solution::{find, SOLUTIONS};
+
+    #[test]
+    fn registry_is_ordered_by_day() {
+        for (index, solution) in SOLUTIONS.iter().enumerate() {
+            assert_eq!(solution.day(), index + 1);
+        }
+    }
+
+    #[test]
+    fn find_by_day() {
+        assert_eq!(find(10).unwrap().name(), "Cathode-Ray Tube");
+        assert!(find(25).is_none());
+    }
+
+    #[test]
+    fn windows_line_endings() {
+        let example = include_str!("resources/test/day_1_elves_inventory_example.txt");
+        let edited = format!("\u{feff}{}\n\n", example.replace('\n', "\r\n"));
+        let day = find(1).unwrap();
+        let answers = |input: &str| day.run(input, &Overrides::default()).unwrap().parts.map(|part| part.answer);
+        assert_eq!(answers(&edited), answers(example));
+    }
+
+    #[test]
+    fn generated_inputs_are_solvable() {
+        for solution in SOLUTIONS {
+            for seed in 0..5 {
+                let input = solution.generate(&mut Random::new(seed), 20);
+                if let Err(err) = solution.run(&input, &Overrides::default()) {
+                    panic!("day {} with seed {}: {}\n{}", solution.day(), seed, err, input);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::path::Path;
+
+use crate::error::{Error, Result};
+use crate::simulation::{Simulation, Traceable};
+
+/// States of a simulation after every step, the first of them is the state before any step
+///
+/// A trace is written as one `step: state` line per state. Empty lines and lines starting with
+/// `#` are skipped, so a trace can start with a comment saying what it is.
+#[derive(Debug, PartialEq)]
+pub struct Trace {
+    pub states: Vec<String>,
+}
+
+impl Trace {
+    /// Run the simulation until it's over or took `limit` steps, recording its state after each of them
+    pub fn record<S: Simulation + Traceable>(simulation: &mut S, limit: usize) -> Result<Trace> {
+        let mut states = vec![simulation.trace()];
+        while states.len() <= limit && simulation.step()? {
+            states.push(simulation.trace());
+        }
+        Ok(Trace { states })
+    }
+
+    /// Parse the lines of a trace, the message says which line is malformed
+    pub fn parse(input: &str) -> std::result::Result<Trace, String> {
+        let mut states = vec![];
+        for (index, line) in input.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |message: String| format!("line {}: {}", index + 1, message);
+            let (step, state) = line.split_once(": ").ok_or_else(|| error("trace lines have to be in the form of 'step: state'".to_string()))?;
+            let step = step.parse::<usize>().map_err(|_| error(format!("couldn't parse step '{}'", step)))?;
+            if step != states.len() {
+                return Err(error(format!("step {} where step {} was expected", step, states.len())));
+            }
+            states.push(state.to_string());
+        }
+        Ok(Trace { states })
+    }
+
+    /// Read and parse a trace file
+    pub fn load(path: &Path) -> Result<Trace> {
+        let error = |message: String| Error::Input { path: path.display().to_string(), message };
+        Trace::parse(&fs::read_to_string(path).map_err(|err| error(err.to_string()))?).map_err(error)
+    }
+}
+
+impl Display for Trace {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (step, state) in self.states.iter().enumerate() {
+            writeln!(f, "{}: {}", step, state)?;
+        }
+        Ok(())
+    }
+}
+
+/// First step where two traces differ, `None` stands for a trace which ended before that step
+#[derive(Debug, PartialEq)]
+pub struct Divergence<'a> {
+    pub step: usize,
+    pub left: Option<&'a str>,
+    pub right: Option<&'a str>,
+}
+
+impl Display for Divergence<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let state = |state: Option<&str>| state.map_or("(trace ended)".to_string(), str::to_string);
+        write!(f, "traces differ at step {}\n  left:  {}\n  right: {}", self.step, state(self.left), state(self.right))
+    }
+}
+
+pub fn diff<'a>(left: &'a Trace, right: &'a Trace) -> Option<Divergence<'a>> {
+    (0..left.states.len().max(right.states.len()))
+        .map(|step| Divergence { step, left: left.states.get(step).map(String::as_str), right: right.states.get(step).map(String::as_str) })
+        .find(|divergence| divergence.left != divergence.right)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Result;
+    use crate::simulation::{Simulation, Traceable};
+    use crate::trace::{diff, Divergence, Trace};
+
+    /// Doubles its value until it's over 20, starting at the given value
+    struct Doubling(usize);
+
+    impl Simulation for Doubling {
+        type State = usize;
+
+        fn step(&mut self) -> Result<bool> {
+            if self.0 > 20 {
+                return Ok(false);
+            }
+            self.0 *= 2;
+            Ok(true)
+        }
+
+        fn snapshot(&self) -> usize {
+            self.0
+        }
+
+        fn restore(&mut self, state: usize) {
+            self.0 = state;
+        }
+    }
+
+    impl Traceable for Doubling {
+        fn trace(&self) -> String {
+            format!("value {}", self.0)
+        }
+    }
+
+    #[test]
+    fn record_and_parse() {
+        let trace = Trace::record(&mut Doubling(3), usize::MAX).unwrap();
+        assert_eq!(trace.to_string(), "0: value 3\n1: value 6\n2: value 12\n3: value 24\n");
+        assert_eq!(Trace::parse(&format!("# doubling\n{}\n", trace)), Ok(trace));
+        assert_eq!(Trace::parse("0: value 3\n2: value 12\n"), Err("line 2: step 2 where step 1 was expected".to_string()));
+        assert_eq!(Trace::parse("# empty\n"), Ok(Trace { states: vec![] }));
+        assert_eq!(Trace::record(&mut Doubling(3), 1).unwrap().states, vec!["value 3", "value 6"]);
+    }
+
+    #[test]
+    fn first_divergence() {
+        let left = Trace::record(&mut Doubling(3), usize::MAX).unwrap();
+        let right = Trace::parse("0: value 3\n1: value 6\n2: value 13\n").unwrap();
+        assert_eq!(diff(&left, &left), None);
+        assert_eq!(diff(&left, &right), Some(Divergence { step: 2, left: Some("value 12"), right: Some("value 13") }));
+
+        let shorter = Trace::parse("0: value 3\n1: value 6\n2: value 12\n").unwrap();
+        let divergence = diff(&left, &shorter).unwrap();
+        assert_eq!(divergence.to_string(), "traces differ at step 3\n  left:  value 24\n  right: (trace ended)");
+    }
+}
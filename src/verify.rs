@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+
+use crate::error::Result;
+use crate::isolate::Outcome;
+use crate::solution::Runnable;
+
+/// Expected answers keyed by day and part
+///
+/// Each line of the file has the form `day part value`, where value is the rest of the line.
+/// Multi-line answers escape newlines as `\n`. Empty lines and lines starting with `#` are skipped.
+pub struct Answers {
+    answers: HashMap<(usize, usize), String>,
+}
+
+impl Answers {
+    /// Parse the answers file, the message says which line is malformed
+    pub fn parse(input: &str) -> std::result::Result<Answers, String> {
+        let mut answers = HashMap::new();
+        for (index, line) in input.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |message: &str| format!("line {}: {} in '{}'", index + 1, message, line);
+            let mut parts = line.splitn(3, ' ');
+            let day = parts.next().and_then(|day| day.parse::<usize>().ok()).ok_or_else(|| error("couldn't parse day"))?;
+            let part = parts.next().and_then(|part| part.parse::<usize>().ok()).ok_or_else(|| error("couldn't parse part"))?;
+            let value = parts.next().ok_or_else(|| error("answer is missing a value"))?;
+            answers.insert((day, part), unescape(value));
+        }
+        Ok(Answers { answers })
+    }
+
+    pub fn get(&self, day: usize, part: usize) -> Option<&String> {
+        self.answers.get(&(day, part))
+    }
+}
+
+fn unescape(value: &str) -> String {
+    let mut result = String::new();
+    let mut chars = value.chars();
+    while let Some(character) = chars.next() {
+        if character == '\\' {
+            match chars.next() {
+                Some('n') => result.push('\n'),
+                Some(other) => result.push(other),
+                None => result.push('\\'),
+            }
+        } else {
+            result.push(character);
+        }
+    }
+    result
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Status {
+    Pass,
+    Fail,
+    Missing,
+}
+
+impl Status {
+    fn label(&self) -> &'static str {
+        match self {
+            Status::Pass => "PASS",
+            Status::Fail => "FAIL",
+            Status::Missing => "MISSING",
+        }
+    }
+}
+
+/// Compare answers of every run with the expected ones, print a line per part and return whether
+/// all of the known answers matched
+///
+/// Solutions which failed, timed out or panicked fail both of their parts.
+pub fn verify(runs: &[(&dyn Runnable, Result<Outcome>)], expected: &Answers) -> bool {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for (solution, outcome) in runs {
+        let run = match outcome {
+            Ok(Outcome::Solved(run)) => run,
+            failed => {
+                let failure = match failed {
+                    Ok(failed) => failed.failure().unwrap(),
+                    Err(err) => format!("error: {}", err),
+                };
+                *counts.entry(Status::Fail.label()).or_default() += 2;
+                println!("{:<7} day {:>2}: {}", Status::Fail.label(), solution.day(), failure);
+                continue;
+            }
+        };
+        for (index, part) in run.parts.iter().enumerate() {
+            let expected_answer = expected.get(solution.day(), index + 1);
+            let status = check(expected_answer, &part.answer);
+            *counts.entry(status.label()).or_default() += 1;
+            if status == Status::Fail {
+                println!(
+                    "{:<7} day {:>2} part {}: expected {:?}, got {:?}",
+                    status.label(), solution.day(), index + 1, expected_answer.unwrap(), part.answer
+                );
+            } else {
+                println!("{:<7} day {:>2} part {}", status.label(), solution.day(), index + 1);
+            }
+        }
+    }
+
+    let count = |label| counts.get(label).copied().unwrap_or(0);
+    println!("{} passed, {} failed, {} missing", count("PASS"), count("FAIL"), count("MISSING"));
+    count("FAIL") == 0
+}
+
+fn check(expected: Option<&String>, answer: &str) -> Status {
+    match expected {
+        Some(expected) if expected == answer => Status::Pass,
+        Some(_) => Status::Fail,
+        None => Status::Missing,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::verify::{check, Answers, Status};
+
+    #[test]
+    fn parse_answers() {
+        let answers = Answers::parse("# day part value\n1 1 24000\n\n5 2 MCD\n10 2 #.\\n.#\n").unwrap();
+        assert_eq!(answers.get(1, 1).unwrap(), "24000");
+        assert_eq!(answers.get(5, 2).unwrap(), "MCD");
+        assert_eq!(answers.get(10, 2).unwrap(), "#.\n.#");
+        assert!(answers.get(1, 2).is_none());
+        assert_eq!(Answers::parse("1 1 24000\n1 x 5\n").err(), Some("line 2: couldn't parse part in '1 x 5'".to_string()));
+    }
+
+    #[test]
+    fn check_answers() {
+        assert_eq!(check(Some(&"42".to_string()), "42"), Status::Pass);
+        assert_eq!(check(Some(&"42".to_string()), "43"), Status::Fail);
+        assert_eq!(check(None, "42"), Status::Missing);
+    }
+}
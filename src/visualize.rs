@@ -0,0 +1,239 @@
+use std::collections::HashSet;
+use std::io::{self, BufRead, Write};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use crate::geometry::Point;
+use crate::grid::SparseGrid;
+
+/// Simulation which can be watched one step at a time, see [Driver](crate::simulation::Driver)
+pub trait Visualizer {
+    /// Advance the simulation by one step, `false` when it's over and nothing happened
+    fn step(&mut self) -> bool;
+
+    /// Return to the state before the last step, `false` when there is none
+    fn back(&mut self) -> bool;
+
+    /// Current state of the simulation
+    fn frame(&self) -> Frame;
+}
+
+/// Single picture of a simulation, empty cells are drawn as `.`
+pub struct Frame {
+    pub cells: SparseGrid<char>,
+    /// Cells drawn highlighted, e.g. the knots of a rope or the search frontier
+    pub highlighted: HashSet<Point>,
+    /// Center of the active region, the viewport follows it
+    pub focus: Point,
+    /// Line shown above the picture
+    pub caption: String,
+}
+
+/// Part of the frame shown on the screen, in characters
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Viewport {
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Viewport {
+    /// Parse a viewport in the form of `80x24`
+    pub fn parse(input: &str) -> Option<Viewport> {
+        let (width, height) = input.split_once('x')?;
+        Some(Viewport { width: width.parse().ok()?, height: height.parse().ok()? }).filter(|viewport| viewport.width > 0 && viewport.height > 0)
+    }
+
+    /// Top left corner of the visible part of a picture spanning `bounds`
+    ///
+    /// The focus is kept in the middle unless that would show what's beyond the edges of the
+    /// picture, pictures smaller than the viewport are shown whole.
+    pub fn corner(&self, (min, max): (Point, Point), focus: Point) -> Point {
+        let axis = |min: isize, max: isize, focus: isize, size: usize| {
+            let size = size as isize;
+            if max - min < size {
+                min
+            } else {
+                (focus - size / 2).clamp(min, max - size + 1)
+            }
+        };
+        Point::new(axis(min.x, max.x, focus.x, self.width), axis(min.y, max.y, focus.y, self.height))
+    }
+}
+
+const HIGHLIGHT: &str = "\x1b[1;33m";
+const RESET: &str = "\x1b[0m";
+const CLEAR_LINE: &str = "\x1b[K";
+
+/// Frame cropped to the viewport, each line is cleared to its end so shorter frames don't leave traces
+pub fn render(frame: &Frame, viewport: Viewport) -> String {
+    let mut screen = format!("{}{}\n", frame.caption, CLEAR_LINE);
+    let Some(bounds) = frame.cells.bounds() else {
+        return screen;
+    };
+    let corner = viewport.corner(bounds, frame.focus);
+    for y in corner.y..(corner.y + viewport.height as isize).min(bounds.1.y + 1) {
+        for x in corner.x..(corner.x + viewport.width as isize).min(bounds.1.x + 1) {
+            let point = Point::new(x, y);
+            let cell = frame.cells.get(point).copied().unwrap_or('.');
+            if frame.highlighted.contains(&point) {
+                screen.push_str(&format!("{}{}{}", HIGHLIGHT, cell, RESET));
+            } else {
+                screen.push(cell);
+            }
+        }
+        screen.push_str(CLEAR_LINE);
+        screen.push('\n');
+    }
+    screen
+}
+
+/// Command typed on the standard input while an animation plays
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Control {
+    TogglePause,
+    Step,
+    Back,
+    Faster,
+    Slower,
+    Quit,
+}
+
+impl Control {
+    pub fn parse(input: &str) -> Option<Control> {
+        match input.trim() {
+            "p" | " " => Some(Control::TogglePause),
+            "" | "s" => Some(Control::Step),
+            "b" => Some(Control::Back),
+            "+" => Some(Control::Faster),
+            "-" => Some(Control::Slower),
+            "q" => Some(Control::Quit),
+            _ => None,
+        }
+    }
+}
+
+/// Controls read from the standard input line by line, the terminal stays in its normal mode
+pub fn stdin_controls() -> Receiver<Control> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if let Some(control) = Control::parse(&line) {
+                if sender.send(control).is_err() {
+                    break;
+                }
+            }
+        }
+    });
+    receiver
+}
+
+/// Plays animations on an ANSI terminal
+pub struct Player {
+    pub viewport: Viewport,
+    /// Steps per second while playing
+    pub speed: f64,
+    pub paused: bool,
+}
+
+impl Player {
+    /// Play the simulation until it's quit, redrawing the screen after every step
+    ///
+    /// A finished simulation stays on the screen so it can still be stepped back, unless there are
+    /// no controls to do so.
+    pub fn play<W: Write>(&mut self, visualizer: &mut dyn Visualizer, controls: &Receiver<Control>, out: &mut W) -> io::Result<()> {
+        write!(out, "\x1b[2J\x1b[?25l")?;
+        let mut steps = 0;
+        let mut finished = false;
+        let mut controls_open = true;
+        loop {
+            let state = if finished { "finished" } else if self.paused { "paused" } else { "playing" };
+            let status = format!("step {} | {} | {} steps/s | p: play/pause, enter: step, b: back, +/-: speed, q: quit", steps, state, self.speed);
+            write!(out, "\x1b[H{}{}\n{}\x1b[J", status, CLEAR_LINE, render(&visualizer.frame(), self.viewport))?;
+            out.flush()?;
+            if finished && !controls_open {
+                break;
+            }
+
+            let delay = Duration::from_secs_f64(1.0 / self.speed);
+            let control = if !controls_open {
+                thread::sleep(delay);
+                Err(RecvTimeoutError::Timeout)
+            } else if self.paused || finished {
+                controls.recv().map_err(|_| RecvTimeoutError::Disconnected)
+            } else {
+                controls.recv_timeout(delay)
+            };
+            let mut forward = false;
+            match control {
+                Ok(Control::TogglePause) => self.paused = !self.paused,
+                Ok(Control::Faster) => self.speed *= 2.0,
+                Ok(Control::Slower) => self.speed = (self.speed / 2.0).max(0.25),
+                Ok(Control::Quit) => break,
+                Ok(Control::Step) => {
+                    self.paused = true;
+                    forward = true;
+                }
+                Ok(Control::Back) => {
+                    self.paused = true;
+                    if visualizer.back() {
+                        finished = false;
+                        steps -= 1;
+                    }
+                }
+                Err(RecvTimeoutError::Disconnected) if controls_open => {
+                    // nobody can unpause it anymore, so it just plays to the end
+                    controls_open = false;
+                    self.paused = false;
+                }
+                Err(_) => forward = true,
+            }
+            if forward {
+                finished = !visualizer.step();
+                if !finished {
+                    steps += 1;
+                }
+            }
+        }
+        write!(out, "\x1b[?25h")?;
+        out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::geometry::Point;
+    use crate::grid::SparseGrid;
+    use crate::visualize::{render, Control, Frame, Viewport};
+
+    #[test]
+    fn viewport_follows_focus() {
+        let viewport = Viewport::parse("4x2").unwrap();
+        let bounds = (Point::new(0, 0), Point::new(9, 9));
+        assert_eq!(viewport.corner(bounds, Point::new(5, 5)), Point::new(3, 4));
+        assert_eq!(viewport.corner(bounds, Point::new(0, 0)), Point::new(0, 0));
+        assert_eq!(viewport.corner(bounds, Point::new(9, 9)), Point::new(6, 8));
+        assert_eq!(viewport.corner((Point::new(-1, 2), Point::new(1, 2)), Point::new(9, 9)), Point::new(-1, 2));
+        assert_eq!(Viewport::parse("0x2"), None);
+    }
+
+    #[test]
+    fn render_cropped_frame() {
+        let mut cells = SparseGrid::new();
+        for x in 0..10 {
+            cells.insert(Point::new(x, 0), char::from_digit(x as u32, 10).unwrap());
+        }
+        cells.insert(Point::new(9, 1), '#');
+        let frame = Frame { cells, highlighted: HashSet::from([Point::new(5, 0)]), focus: Point::new(5, 0), caption: "sand".to_string() };
+        let screen = render(&frame, Viewport { width: 3, height: 5 });
+        assert_eq!(screen.replace("\x1b[K", ""), "sand\n4\x1b[1;33m5\x1b[0m6\n...\n");
+        assert_eq!(Control::parse(""), Some(Control::Step));
+        assert_eq!(Control::parse("b"), Some(Control::Back));
+        assert_eq!(Control::parse("x"), None);
+    }
+}
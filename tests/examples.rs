@@ -0,0 +1,66 @@
+//! Runs every example in `src/resources/test/` which has expected answers next to it
+//!
+//! An example `day_N_<name>.txt` is checked against `day_N_<name>.answers`, which uses the same
+//! `day part value` format as `answers.txt`. Parts without an expected answer aren't checked and
+//! examples without the sidecar file are skipped.
+
+use std::fs;
+use std::path::Path;
+
+use advent_of_code_2022::params::Overrides;
+use advent_of_code_2022::solution;
+use advent_of_code_2022::verify::Answers;
+
+#[test]
+fn examples() {
+    let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/resources/test");
+    let mut paths = fs::read_dir(&directory)
+        .expect("Couldn't read test resources")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "txt"))
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    let mut checked = 0;
+    let mut failures = vec![];
+    for path in paths {
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        let Some(day) = day_of(&name) else {
+            continue;
+        };
+        let Ok(sidecar) = fs::read_to_string(path.with_extension("answers")) else {
+            continue;
+        };
+        let expected = Answers::parse(&sidecar).unwrap_or_else(|message| panic!("{}: {}", name, message));
+        let solution = solution::find(day).unwrap_or_else(|| panic!("{}: there is no solution for day {}", name, day));
+
+        let run = match solution.run(&fs::read_to_string(&path).unwrap(), &Overrides::default()) {
+            Ok(run) => run,
+            Err(err) => {
+                failures.push(format!("{}: {}", name, err));
+                continue;
+            }
+        };
+        let mut parts = 0;
+        for (index, part) in run.parts.iter().enumerate() {
+            if let Some(answer) = expected.get(day, index + 1) {
+                parts += 1;
+                if *answer != part.answer {
+                    failures.push(format!("{} part {}: expected {:?}, got {:?}", name, index + 1, answer, part.answer));
+                }
+            }
+        }
+        if parts == 0 {
+            failures.push(format!("{}: sidecar has no answers for day {}", name, day));
+        }
+        checked += 1;
+    }
+
+    assert!(checked > 0, "No examples with expected answers were found in {}", directory.display());
+    assert!(failures.is_empty(), "{} example(s) failed:\n{}", failures.len(), failures.join("\n"));
+}
+
+/// Day number of an example named `day_N_<name>.txt`
+fn day_of(name: &str) -> Option<usize> {
+    name.strip_prefix("day_")?.split_once('_')?.0.parse().ok()
+}